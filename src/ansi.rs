@@ -79,6 +79,31 @@ impl Style {
     }
 }
 
+#[allow(unused)]
 pub fn clear_screen() {
     println!("{0}2J{0};H", CSI)
 }
+
+/// Moves the cursor to the top left corner.
+pub fn cursor_home() -> String {
+    format!("{}H", CSI)
+}
+
+/// Clears the rest of the line the cursor is in.
+pub fn clear_line_end() -> String {
+    format!("{}K", CSI)
+}
+
+/// Clears everything below the cursor.
+pub fn clear_below() -> String {
+    format!("{}J", CSI)
+}
+
+pub fn show_cursor(show: bool) -> String {
+    format!("{}?25{}", CSI, if show { 'h' } else { 'l' })
+}
+
+/// Switches to the alternate screen used by full-screen programs, or back to the normal one.
+pub fn alternate_screen(enable: bool) -> String {
+    format!("{}?1049{}", CSI, if enable { 'h' } else { 'l' })
+}
@@ -0,0 +1,19 @@
+extern crate amazons;
+use amazons::{protocol::Engine, *};
+use std::{env, io, process};
+
+fn main() {
+    let spec = env::args().nth(1).unwrap_or_else(|| "Minimax(2)".into());
+    let strategy = strat::from_spec(&spec).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        eprintln!("usage: engine [strategy]");
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(err) = Engine::new(strategy).run(stdin.lock(), stdout.lock()) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -4,15 +4,16 @@ use amazons::*;
 fn main() {
     repeat_games(
         vec![
-            Box::new(strat::LookAhead),
+            Box::new(strat::LookAhead::new()),
             Box::new(strat::DeepIdiot::with_depth(5)),
         ],
         10,
         false,
+        std::thread::available_parallelism().map_or(1, |n| n.get()),
     );
-    let mut game = Game::new(
+    let _game = Game::new(
         Box::new(strat::Minimax::with_depth(2)),
         Box::new(strat::DeepIdiot::with_depth(4)),
     );
-    //show::display_game(&mut game.as_iter());
+    //show::display_game(&mut game.as_iter().map_while(Result::ok), std::time::Duration::from_millis(100));
 }
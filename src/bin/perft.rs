@@ -0,0 +1,40 @@
+extern crate amazons;
+use amazons::*;
+use std::{env, process, time::Instant};
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let depth = match args.first().map(|d| d.parse::<usize>()) {
+        Some(Ok(depth)) => depth,
+        _ => {
+            eprintln!("usage: perft <depth> [divide] [position]");
+            process::exit(1);
+        }
+    };
+    let divide = args.get(1).is_some_and(|a| a == "divide");
+    let fen = args[if divide { 2 } else { 1 }..].join(" ");
+
+    let mut state = if fen.is_empty() {
+        GameState::new()
+    } else {
+        GameState::from_fen(&fen).unwrap_or_else(|err| {
+            eprintln!("invalid position: {}", err);
+            process::exit(1);
+        })
+    };
+    let start = Instant::now();
+
+    let total = if divide {
+        let counts = perft::divide(&mut state, depth);
+        for (mov, count) in &counts {
+            println!("{}: {}", mov, count);
+        }
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        perft::perft(&mut state, depth)
+    };
+
+    let secs = start.elapsed().as_secs_f64();
+    println!("perft({}) = {}", depth, total);
+    println!("{:.3}s, {:.0} moves/s", secs, total as f64 / secs);
+}
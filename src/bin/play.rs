@@ -0,0 +1,37 @@
+extern crate amazons;
+use amazons::*;
+use std::{env, process};
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let spec = |i: usize, default: &str| {
+        let spec = args.get(i).map_or(default, String::as_str);
+        strat::from_spec(spec).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            eprintln!("usage: play [white strategy] [black strategy]");
+            process::exit(1);
+        })
+    };
+    let mut game = Game::new(spec(0, "Human"), spec(1, "Minimax(2)"));
+
+    let (winner, termination) = loop {
+        if let Some(outcome) = game.outcome() {
+            break outcome;
+        }
+        let player = game.state().turn();
+        match game.next_move() {
+            Ok(Action::Move(mov)) => {
+                println!("{:?} plays {} ({})", player, mov, mov.community_notation())
+            }
+            Ok(Action::Undo) => println!("{:?} takes back a move", player),
+            Ok(Action::Resign) => println!("{:?} resigns", player),
+            Err(err) if err.failure == MoveFailure::NothingToUndo => eprintln!("{}", err),
+            Err(err) => {
+                eprintln!("{}", err);
+                game.forfeit(err.player, err.termination());
+            }
+        }
+    };
+    println!("{}", game.state().board().display(&BoardStyle::default()));
+    println!("{:?} wins ({})", winner, termination);
+}
@@ -0,0 +1,45 @@
+extern crate amazons;
+use amazons::{tui::Tui, *};
+use std::{env, fs, process};
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let fail = |msg: String| -> ! {
+        eprintln!("{}", msg);
+        eprintln!("usage: replay <record or sgf file> [game number]");
+        process::exit(1);
+    };
+    let path = args.first().unwrap_or_else(|| fail("no file given".into()));
+    let number = match args.get(1) {
+        Some(n) => n
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .unwrap_or_else(|| fail(format!("'{}' is not a game number", n))),
+        None => 1,
+    };
+
+    let text = fs::read_to_string(path).unwrap_or_else(|err| fail(format!("{}: {}", path, err)));
+    let records = if path.to_lowercase().ends_with(".sgf") {
+        parse_collection(&text)
+            .and_then(|trees| {
+                trees
+                    .iter()
+                    .map(|root| GameRecord::from_sgf_node(root).map(|(record, _)| record))
+                    .collect()
+            })
+            .map_err(|err| err.to_string())
+    } else {
+        GameRecord::parse_all(&text).map_err(|err| err.to_string())
+    };
+    let records = records.unwrap_or_else(|err| fail(format!("{}: {}", path, err)));
+    let record = records
+        .get(number - 1)
+        .unwrap_or_else(|| fail(format!("{} contains only {} games", path, records.len())));
+
+    let mut tui = Tui::from_record(record).unwrap_or_else(|err| fail(err));
+    if let Err(err) = tui.run() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,28 @@
+extern crate amazons;
+use amazons::{tui::Tui, *};
+use std::{env, process};
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    // players without a strategy are controlled in the interface
+    let player = |i: usize, default: &str| {
+        let spec = args.get(i).map_or(default, String::as_str);
+        if spec.trim().eq_ignore_ascii_case("human") {
+            return None;
+        }
+        Some(strat::from_spec(spec).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            eprintln!("usage: tui [white strategy] [black strategy]");
+            process::exit(1);
+        }))
+    };
+    let mut tui = Tui::new(
+        GameState::new(),
+        player(0, "Human"),
+        player(1, "Minimax(2)"),
+    );
+    if let Err(err) = tui.run() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
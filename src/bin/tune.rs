@@ -0,0 +1,51 @@
+extern crate amazons;
+use amazons::{strat::*, tune::Tuner};
+use std::{env, process, thread};
+
+const DEFAULT_WEIGHTS: &str = "Weighted(Mobility: 1..0, Territory: 0.5..1)";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (checkpoint, generations) = match (args.first(), args.get(1).map(|n| n.parse::<usize>())) {
+        (Some(checkpoint), None) => (checkpoint, 100),
+        (Some(checkpoint), Some(Ok(generations))) => (checkpoint, generations),
+        _ => {
+            eprintln!("usage: tune <checkpoint> [generations] [weights]");
+            process::exit(1);
+        }
+    };
+    let weights = args[2.min(args.len())..].join(" ");
+    let weights = if weights.is_empty() {
+        DEFAULT_WEIGHTS
+    } else {
+        &weights
+    };
+    let weights = weights.parse::<Weighted>().unwrap_or_else(|err| {
+        eprintln!("invalid weights: {}", err);
+        process::exit(1);
+    });
+
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut tuner = Tuner::new(weights).threads(threads);
+    if std::path::Path::new(checkpoint).exists() {
+        tuner = tuner.load(checkpoint).unwrap_or_else(|err| {
+            eprintln!("can't resume from {}: {}", checkpoint, err);
+            process::exit(1);
+        });
+        println!("resuming at generation {}", tuner.generation());
+    }
+
+    for _ in 0..generations {
+        let better = tuner.step();
+        if let Err(err) = tuner.save(checkpoint) {
+            eprintln!("can't save {}: {}", checkpoint, err);
+            process::exit(1);
+        }
+        println!(
+            "generation {}: {}",
+            tuner.generation(),
+            if better { "better" } else { "no better" }
+        );
+    }
+    println!("best: {}", tuner.best().name());
+}
@@ -0,0 +1,286 @@
+use super::*;
+use core::ops::*;
+
+/// The largest supported board side length.
+pub const MAX_BOARD_LEN: usize = 12;
+
+const STRIDE: usize = 16;
+const ROWS_PER_LIMB: usize = 64 / STRIDE;
+const LIMBS: usize = MAX_BOARD_LEN / ROWS_PER_LIMB;
+
+const fn rows(len: usize) -> [u64; LIMBS] {
+    let mut limbs = [0; LIMBS];
+    let mut y = 0;
+    while y < len {
+        limbs[y / ROWS_PER_LIMB] |= ((1 << len) - 1) << (y % ROWS_PER_LIMB * STRIDE);
+        y += 1;
+    }
+    limbs
+}
+const MAX_BOARD: [u64; LIMBS] = rows(MAX_BOARD_LEN);
+
+/// A set of squares. Each row takes 16 bits, of which the first `MAX_BOARD_LEN` are used,
+/// and four rows are packed into one `u64`.
+///
+/// Squares outside a board smaller than the maximum are representable, so the results of
+/// [`Not`] and [`Bitboard::king_moves`] have to be masked with [`Bitboard::board`] by the
+/// caller. Sliding and flooding over a set of empty squares of the board stays on the
+/// board by itself.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Bitboard([u64; LIMBS]);
+
+impl Bitboard {
+    pub const EMPTY: Self = Self([0; LIMBS]);
+
+    /// All squares of a board with side length `len`.
+    #[inline]
+    pub fn board(len: usize) -> Self {
+        Self(rows(len))
+    }
+
+    #[inline]
+    fn limb_bit(p: Pos) -> (usize, usize) {
+        let y = p.y as usize;
+        (y / ROWS_PER_LIMB, y % ROWS_PER_LIMB * STRIDE + p.x as usize)
+    }
+
+    #[inline]
+    pub fn square(p: Pos) -> Self {
+        let (limb, bit) = Self::limb_bit(p);
+        let mut limbs = [0; LIMBS];
+        limbs[limb] = 1 << bit;
+        Self(limbs)
+    }
+
+    #[inline]
+    pub fn has(self, p: Pos) -> bool {
+        let (limb, bit) = Self::limb_bit(p);
+        self.0[limb] >> bit & 1 == 1
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self == Self::EMPTY
+    }
+
+    #[inline]
+    pub fn count(self) -> u32 {
+        self.0.iter().map(|limb| limb.count_ones()).sum()
+    }
+
+    /// Returns the square with the lowest row and column.
+    #[inline]
+    pub fn first(self) -> Option<Pos> {
+        let limb = self.0.iter().position(|&limb| limb != 0)?;
+        let bit = self.0[limb].trailing_zeros() as usize;
+        let y = limb * ROWS_PER_LIMB + bit / STRIDE;
+        Some(((bit % STRIDE) as u8, y as u8).into())
+    }
+
+    /// Removes the square [`Bitboard::first`] would return.
+    #[inline]
+    fn pop_first(&mut self) {
+        if let Some(limb) = self.0.iter_mut().find(|limb| **limb != 0) {
+            *limb &= *limb - 1;
+        }
+    }
+
+    /// Moves every square one step in direction `dir`, dropping squares that leave the maximum board.
+    ///
+    /// `dir` can't be `(0, 0)`, and it can go at most 4 squares sideways and 3 rows up or down:
+    /// further, squares would wrap into the next row or skip a whole part of the board.
+    #[inline]
+    pub fn shift(self, (dx, dy): (i8, i8)) -> Self {
+        debug_assert!(
+            (dx, dy) != (0, 0)
+                && dx.unsigned_abs() as usize <= STRIDE - MAX_BOARD_LEN
+                && (dy.unsigned_abs() as usize) < ROWS_PER_LIMB,
+            "can't shift by {:?}",
+            (dx, dy)
+        );
+        let amount = dy as i32 * STRIDE as i32 + dx as i32;
+        let [a0, a1, a2] = self.0;
+        let limbs = if amount > 0 {
+            let n = amount as u32;
+            [a0 << n, a1 << n | a0 >> (64 - n), a2 << n | a1 >> (64 - n)]
+        } else {
+            let n = -amount as u32;
+            [a0 >> n | a1 << (64 - n), a1 >> n | a2 << (64 - n), a2 >> n]
+        };
+        Self(limbs) & Self(MAX_BOARD)
+    }
+
+    /// All squares that are one king step away from a square in the set.
+    /// Squares off a board smaller than the maximum are included.
+    pub fn king_moves(self) -> Self {
+        DIRECTIONS
+            .iter()
+            .fold(Self::EMPTY, |acc, &dir| acc | self.shift(dir))
+    }
+
+    /// All squares reachable by sliding from the set in direction `dir` over `empty` squares.
+    #[inline]
+    pub fn ray(self, dir: (i8, i8), empty: Self) -> Self {
+        let mut res = Self::EMPTY;
+        let mut front = self.shift(dir) & empty;
+        while !front.is_empty() {
+            res |= front;
+            front = front.shift(dir) & empty;
+        }
+        res
+    }
+
+    /// All squares reachable by a queen move from the set over `empty` squares.
+    pub fn queen_moves(self, empty: Self) -> Self {
+        DIRECTIONS
+            .iter()
+            .fold(Self::EMPTY, |acc, &dir| acc | self.ray(dir, empty))
+    }
+
+    /// All `empty` squares connected to the set by king steps over `empty` squares.
+    pub fn flood(self, empty: Self) -> Self {
+        let mut res = self.king_moves() & empty;
+        loop {
+            let next = res | (res.king_moves() & empty);
+            if next == res {
+                return res;
+            }
+            res = next;
+        }
+    }
+
+    #[inline]
+    pub fn iter(self) -> Squares {
+        Squares(self)
+    }
+}
+
+impl From<Pos> for Bitboard {
+    fn from(p: Pos) -> Self {
+        Self::square(p)
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Pos;
+    type IntoIter = Squares;
+    fn into_iter(self) -> Squares {
+        self.iter()
+    }
+}
+
+macro_rules! __bit_op__ {
+    ($($op:ident $fn:ident $op_assign:ident $fn_assign:ident),*) => {$(
+        impl $op for Bitboard {
+            type Output = Self;
+            #[inline]
+            fn $fn(mut self, other: Self) -> Self {
+                self.$fn_assign(other);
+                self
+            }
+        }
+        impl $op_assign for Bitboard {
+            #[inline]
+            fn $fn_assign(&mut self, other: Self) {
+                for (a, b) in self.0.iter_mut().zip(other.0) {
+                    a.$fn_assign(b);
+                }
+            }
+        }
+    )*}
+}
+__bit_op__!(
+    BitAnd bitand BitAndAssign bitand_assign,
+    BitOr bitor BitOrAssign bitor_assign,
+    BitXor bitxor BitXorAssign bitxor_assign
+);
+
+impl Not for Bitboard {
+    type Output = Self;
+    #[inline]
+    fn not(mut self) -> Self {
+        for (limb, mask) in self.0.iter_mut().zip(MAX_BOARD) {
+            *limb = !*limb & mask;
+        }
+        self
+    }
+}
+
+/// Iterator over the squares of a [`Bitboard`], row by row.
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = Pos;
+    #[inline]
+    fn next(&mut self) -> Option<Pos> {
+        let p = self.0.first()?;
+        self.0.pop_first();
+        Some(p)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.0.count() as usize;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+#[cfg(test)]
+fn squares(squares: &[(u8, u8)]) -> Bitboard {
+    squares
+        .iter()
+        .fold(Bitboard::EMPTY, |acc, &p| acc | Bitboard::square(p.into()))
+}
+
+#[test]
+fn shift_wraps() {
+    // off the right and left edge of the maximum board, into the unused bits of the row stride
+    assert!(squares(&[(11, 0)]).shift((1, 0)).is_empty());
+    assert!(squares(&[(11, 5)]).shift((1, -1)).is_empty());
+    assert!(squares(&[(0, 3)]).shift((-1, 0)).is_empty());
+    assert!(squares(&[(0, 4)]).shift((-1, -1)).is_empty());
+    // off the top and bottom
+    assert!(squares(&[(5, 0)]).shift((0, -1)).is_empty());
+    assert!(squares(&[(5, 11)]).shift((1, 1)).is_empty());
+    // across the rows packed into different limbs
+    assert_eq!(squares(&[(5, 3)]).shift((1, 1)), squares(&[(6, 4)]));
+    assert_eq!(squares(&[(0, 8)]).shift((0, -1)), squares(&[(0, 7)]));
+    assert_eq!(squares(&[(11, 7)]).shift((-1, 1)), squares(&[(10, 8)]));
+    // the largest steps there are
+    assert_eq!(squares(&[(1, 2)]).shift((4, 3)), squares(&[(5, 5)]));
+    assert_eq!(squares(&[(4, 5)]).shift((-4, -3)), squares(&[(0, 2)]));
+    assert!(squares(&[(8, 0), (0, 9)]).shift((4, 3)).is_empty());
+    assert!(squares(&[(3, 3)]).shift((-4, 0)).is_empty());
+}
+
+#[test]
+fn ray() {
+    let empty = !squares(&[(5, 0), (3, 3)]);
+    let corner = squares(&[(0, 0)]);
+    assert_eq!(
+        corner.ray((1, 0), empty),
+        squares(&[(1, 0), (2, 0), (3, 0), (4, 0)])
+    );
+    assert_eq!(corner.ray((1, 1), empty), squares(&[(1, 1), (2, 2)]));
+    assert_eq!(corner.ray((0, 1), empty).count(), 11);
+    assert!(corner.ray((-1, 0), empty).is_empty());
+    // stays on a smaller board when the empty squares do
+    let small = Bitboard::board(6);
+    assert_eq!(corner.ray((0, 1), small & empty).count(), 5);
+}
+
+#[test]
+fn flood() {
+    let corner = squares(&[(0, 0)]);
+    let wall = (0..11).map(|x| (x, 6)).collect::<Vec<_>>();
+    let empty = !squares(&wall) & !corner;
+    // the wall is only open at the right, so all but the start and the wall is reached
+    assert_eq!(corner.flood(empty).count(), 144 - 11 - 1);
+
+    // closed, the rows above it are reached across the limb boundary after row 3
+    let closed = empty & !squares(&[(11, 6)]);
+    let reached = corner.flood(closed);
+    assert_eq!(reached.count(), 6 * 12 - 1);
+    assert!(reached.has((11, 5).into()));
+    assert!(!reached.has((11, 7).into()));
+}
@@ -2,25 +2,112 @@ use super::*;
 use crate::ansi::*;
 use core::ops::*;
 
-pub const BOARD_LEN: usize = 10;
+/// The side length of the standard board.
+pub const DEFAULT_BOARD_LEN: usize = 10;
+
+/// A square board of side length up to [`MAX_BOARD_LEN`],
+/// stored as one set of squares per kind of piece.
+#[derive(PartialEq, Eq, Copy, Clone)]
+pub struct Board {
+    len: u8,
+    amazons: [Bitboard; 2],
+    arrows: Bitboard,
+}
 
-#[derive(PartialEq, Eq, Copy, Clone, Default)]
-pub struct Board([[Slot; BOARD_LEN]; BOARD_LEN]);
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(DEFAULT_BOARD_LEN)
+    }
+}
 
 impl Board {
+    /// Creates an empty board with side length `len`.
+    pub fn new(len: usize) -> Self {
+        assert!(
+            (1..=MAX_BOARD_LEN).contains(&len),
+            "board side length {} is not in 1..={}",
+            len,
+            MAX_BOARD_LEN
+        );
+        Self {
+            len: len as u8,
+            amazons: [Bitboard::EMPTY; 2],
+            arrows: Bitboard::EMPTY,
+        }
+    }
+
+    /// The side length of the board.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.len as usize
+    }
+
+    /// All squares of the board.
+    #[inline]
+    pub fn squares(&self) -> Bitboard {
+        Bitboard::board(self.size())
+    }
+
+    #[inline]
+    pub fn get(&self, p: Pos) -> Slot {
+        if self.arrows.has(p) {
+            Slot::Arrow
+        } else if self.amazons[Player::White as usize].has(p) {
+            Slot::Amazon(Player::White)
+        } else if self.amazons[Player::Black as usize].has(p) {
+            Slot::Amazon(Player::Black)
+        } else {
+            Slot::Empty
+        }
+    }
+
+    #[inline]
+    pub fn set(&mut self, p: impl Into<Pos>, slot: Slot) {
+        let square = Bitboard::square(p.into());
+        self.arrows &= !square;
+        self.amazons[0] &= !square;
+        self.amazons[1] &= !square;
+        match slot {
+            Slot::Empty => {}
+            Slot::Arrow => self.arrows |= square,
+            Slot::Amazon(player) => self.amazons[player as usize] |= square,
+        }
+    }
+
+    #[inline]
+    pub fn amazons(&self, player: Player) -> Bitboard {
+        self.amazons[player as usize]
+    }
+
+    #[inline]
+    pub fn arrows(&self) -> Bitboard {
+        self.arrows
+    }
+
+    #[inline]
+    pub fn occupied(&self) -> Bitboard {
+        self.arrows | self.amazons[0] | self.amazons[1]
+    }
+
+    #[inline]
+    pub fn empty(&self) -> Bitboard {
+        self.squares() & !self.occupied()
+    }
+
     pub fn is_trapped(&self, p: Pos) -> bool {
-        p.neighbors().into_iter().all(|p| !self[p].is_empty())
+        (Bitboard::square(p).king_moves() & self.empty()).is_empty()
     }
 
     pub fn display(&self, styles: &BoardStyle) -> String {
-        let mut buf = "  ".to_string();
-        for x in 0..BOARD_LEN as u8 {
+        let width = (self.size() - 1).to_string().len();
+        let mut buf = format!("{:w$} ", "", w = width);
+        for x in 0..self.len {
             buf += &format!("{} ", (0x61u8 + x) as char);
         }
         buf += "\n";
-        for y in 0..BOARD_LEN {
-            buf += &format!("{} ", y);
-            for x in 0..BOARD_LEN {
+        for y in 0..self.size() {
+            buf += &format!("{:>w$} ", y, w = width);
+            for x in 0..self.size() {
                 let style = if x % 2 == y % 2 {
                     &styles.checker_light
                 } else {
@@ -41,105 +128,53 @@ impl Board {
     #[inline]
     pub fn contains(&self, pos: impl Into<Pos>) -> bool {
         let pos = pos.into();
-        pos.x < BOARD_LEN as _ && pos.y < BOARD_LEN as _
+        pos.x < self.len && pos.y < self.len
     }
 
     /// Returns a vector of the furthest possible reachable positions
     pub fn reachable_from(&self, p: Pos) -> Vec<Pos> {
-        let dirs = [0u8, 1u8, u8::MAX];
-
+        let empty = self.empty();
         let mut res = Vec::with_capacity(8);
-        for &dir_x in &dirs {
-            for &dir_y in &dirs {
-                if (dir_x, dir_y) == (0, 0) {
-                    continue;
-                }
-
-                let mut q = (p.x, p.y);
-                let mut n;
-                let mut possible = false;
-                while {
-                    n = (q.0.wrapping_add(dir_x), q.1.wrapping_add(dir_y));
-                    self.contains(n) && self[n].is_empty()
-                } {
-                    q = n;
-                    possible = true;
-                }
-
-                if possible {
-                    res.push(q.into())
-                }
+        for &(dx, dy) in &DIRECTIONS {
+            let steps = Bitboard::square(p).ray((dx, dy), empty).count() as u8;
+            if steps > 0 {
+                res.push(
+                    (
+                        p.x.wrapping_add((dx as u8).wrapping_mul(steps)),
+                        p.y.wrapping_add((dy as u8).wrapping_mul(steps)),
+                    )
+                        .into(),
+                );
             }
         }
-
         res
     }
 
     pub fn reach_count(&self, p: Pos) -> usize {
-        let mut count = 0;
-
-        macro_rules! _loop {
-            ($count:ident, $p:ident, $( ( $opx:tt $valx:expr , $opy:tt $valy:expr ) ),*) => {
-                let mut q;
-                $(
-                    q = $p;
-                    while {
-                        q.x = q.x $opx $valx;
-                        q.y = q.y $opy $valy;
-                        self.contains(q) && self[q].is_empty()
-                    } {
-                        $count += 1;
-                    }
-                )*
-            }
-        };
-
-        _loop!(count, p,
-            (- 1 , - 1),
-            (- 1 , + 0),
-            (- 1 , + 1),
-            (+ 0 , - 1),
-            (+ 0 , + 1),
-            (+ 1 , - 1),
-            (+ 1 , + 0),
-            (+ 1 , + 1)
-        );
-
-        count
+        Bitboard::square(p).queen_moves(self.empty()).count() as usize
     }
 
     pub fn find_amazons(&self, player: Player) -> Vec<Pos> {
-        let mut res = Vec::with_capacity(4);
-        for x in 0..BOARD_LEN {
-            for y in 0..BOARD_LEN {
-                let (x, y) = (x as u8, y as u8);
-                if self[(x, y)] == Slot::Amazon(player) {
-                    res.push((x, y).into());
-                }
-            }
-        }
-        res
+        self.amazons(player).iter().collect()
     }
 }
 
 impl<T: Into<Pos>> Index<T> for Board {
     type Output = Slot;
     fn index(&self, pos: T) -> &Slot {
-        let pos = pos.into();
-        &self.0[pos.x as usize][pos.y as usize]
-    }
-}
-
-impl<T: Into<Pos>> IndexMut<T> for Board {
-    fn index_mut(&mut self, pos: T) -> &mut Slot {
-        let pos = pos.into();
-        &mut self.0[pos.x as usize][pos.y as usize]
+        match self.get(pos.into()) {
+            Slot::Empty => &Slot::Empty,
+            Slot::Arrow => &Slot::Arrow,
+            Slot::Amazon(Player::White) => &Slot::Amazon(Player::White),
+            Slot::Amazon(Player::Black) => &Slot::Amazon(Player::Black),
+        }
     }
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
 #[repr(u8)]
 pub enum Slot {
+    #[default]
     Empty,
     Arrow,
     Amazon(Player),
@@ -152,12 +187,6 @@ impl Slot {
     }
 }
 
-impl Default for Slot {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 pub struct BoardStyle {
     pub checker_light: Style,
     pub checker_dark: Style,
@@ -189,7 +218,7 @@ fn correct_distance() {
         let x = rng.gen_range(0..10);
         let y = rng.gen_range(0..10);
         if board[(x, y)].is_empty() {
-            board[(x, y)] = Slot::Arrow;
+            board.set((x, y), Slot::Arrow);
         }
     }
 
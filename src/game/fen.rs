@@ -0,0 +1,195 @@
+//! A compact text notation for positions, modelled after chess FEN.
+//!
+//! The position is written rank by rank starting at rank 0, separated by `/`.
+//! `W` and `B` are amazons, `x` is an arrow and a number stands for that many empty squares.
+//! The ranks are followed by the player to move (`w` or `b`) and the side length of the board,
+//! so the standard starting position is `3B2B3/10/10/B8B/10/10/W8W/10/10/3W2W3 w 10`.
+
+use super::*;
+use std::{error, fmt};
+
+impl GameState {
+    pub fn to_fen(&self) -> String {
+        let len = self.board().size();
+        let mut ranks = Vec::with_capacity(len);
+        for y in 0..len as u8 {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for x in 0..len as u8 {
+                let c = match self.board()[(x, y)] {
+                    Slot::Empty => {
+                        empty += 1;
+                        continue;
+                    }
+                    Slot::Arrow => 'x',
+                    Slot::Amazon(Player::White) => 'W',
+                    Slot::Amazon(Player::Black) => 'B',
+                };
+                if empty > 0 {
+                    rank += &empty.to_string();
+                    empty = 0;
+                }
+                rank.push(c);
+            }
+            if empty > 0 {
+                rank += &empty.to_string();
+            }
+            ranks.push(rank);
+        }
+        let turn = match self.turn() {
+            Player::White => 'w',
+            Player::Black => 'b',
+        };
+        format!("{} {} {}", ranks.join("/"), turn, len)
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, ParseFenError> {
+        let mut fields = fen.split_whitespace();
+        let ranks = fields.next().ok_or(ParseFenError::MissingField("ranks"))?;
+        let turn = fields.next().ok_or(ParseFenError::MissingField("turn"))?;
+        let size = fields.next().ok_or(ParseFenError::MissingField("size"))?;
+        if let Some(extra) = fields.next() {
+            return Err(ParseFenError::TrailingInput(extra.into()));
+        }
+
+        let len = size
+            .parse::<usize>()
+            .ok()
+            .filter(|len| (1..=MAX_BOARD_LEN).contains(len))
+            .ok_or_else(|| ParseFenError::InvalidSize(size.into()))?;
+        let turn = match turn {
+            "w" => Player::White,
+            "b" => Player::Black,
+            _ => return Err(ParseFenError::InvalidTurn(turn.into())),
+        };
+
+        let ranks = ranks.split('/').collect::<Vec<_>>();
+        if ranks.len() != len {
+            return Err(ParseFenError::RankCount(ranks.len(), len));
+        }
+
+        let mut board = Board::new(len);
+        for (y, rank) in ranks.into_iter().enumerate() {
+            let mut x = 0;
+            let mut chars = rank.chars().peekable();
+            while let Some(c) = chars.next() {
+                let slot = match c {
+                    'x' => Slot::Arrow,
+                    'W' => Slot::Amazon(Player::White),
+                    'B' => Slot::Amazon(Player::Black),
+                    '0'..='9' => {
+                        let mut empty = c.to_digit(10).unwrap() as usize;
+                        while let Some(d) = chars.peek().and_then(|d| d.to_digit(10)) {
+                            chars.next();
+                            let longer = empty
+                                .checked_mul(10)
+                                .and_then(|e| e.checked_add(d as usize));
+                            empty = match longer {
+                                Some(longer) if x + longer <= len => longer,
+                                // too long for the rank, which is reported before it can overflow
+                                _ => {
+                                    let found = x + empty * 10 + d as usize;
+                                    return Err(ParseFenError::RankLength(y, found, len));
+                                }
+                            };
+                        }
+                        x += empty;
+                        continue;
+                    }
+                    _ => return Err(ParseFenError::InvalidChar(c)),
+                };
+                if x < len {
+                    board.set((x as u8, y as u8), slot);
+                }
+                x += 1;
+            }
+            if x != len {
+                return Err(ParseFenError::RankLength(y, x, len));
+            }
+        }
+
+        Ok(Self::from_board(board, turn))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseFenError {
+    MissingField(&'static str),
+    TrailingInput(String),
+    InvalidSize(String),
+    InvalidTurn(String),
+    /// The number of ranks found and the number expected from the board size.
+    RankCount(usize, usize),
+    InvalidChar(char),
+    /// The rank, the number of squares found in it and the number expected.
+    RankLength(usize, usize, usize),
+}
+
+impl fmt::Display for ParseFenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "the {} field is missing", field),
+            Self::TrailingInput(s) => write!(f, "unexpected '{}' after the size", s),
+            Self::InvalidSize(s) => write!(
+                f,
+                "'{}' is not a board size between 1 and {}",
+                s, MAX_BOARD_LEN
+            ),
+            Self::InvalidTurn(s) => {
+                write!(f, "expected 'w' or 'b' as player to move, found '{}'", s)
+            }
+            Self::RankCount(found, expected) => {
+                write!(f, "found {} ranks instead of {}", found, expected)
+            }
+            Self::InvalidChar(c) => write!(f, "'{}' is neither a square count nor a piece", c),
+            Self::RankLength(rank, found, expected) => write!(
+                f,
+                "rank {} has {} squares instead of {}",
+                rank, found, expected
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseFenError {}
+
+#[test]
+fn fen_start_position() {
+    let fen = "3B2B3/10/10/B8B/10/10/W8W/10/10/3W2W3 w 10";
+    assert_eq!(GameState::new().to_fen(), fen);
+    assert!(GameState::from_fen(fen) == Ok(GameState::new()));
+}
+
+#[test]
+fn fen_round_trip() {
+    use rand::prelude::*;
+    let mut rng = rand::thread_rng();
+
+    for len in [4, 6, 10, 11, 12] {
+        let mut state = GameState::with_size(len);
+        for _ in 0..20 {
+            let fen = state.to_fen();
+            assert!(GameState::from_fen(&fen) == Ok(state.clone()), "{}", fen);
+
+            let moves = state.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            state.do_move(moves[rng.gen_range(0..moves.len())]);
+        }
+    }
+}
+
+#[test]
+fn fen_errors() {
+    let parse = GameState::from_fen;
+    assert!(parse("4/4/4/4 w").is_err_and(|e| e == ParseFenError::MissingField("size")));
+    assert!(parse("4/4/4/4 w 5").is_err_and(|e| e == ParseFenError::RankCount(4, 5)));
+    assert!(parse("4/4/4/4 w 13").is_err_and(|e| e == ParseFenError::InvalidSize("13".into())));
+    assert!(parse("4/4/4/4 x 4").is_err_and(|e| e == ParseFenError::InvalidTurn("x".into())));
+    assert!(parse("4/4/W2B1/4 w 4").is_err_and(|e| e == ParseFenError::RankLength(2, 5, 4)));
+    assert!(parse("4/4/W2Q/4 b 4").is_err_and(|e| e == ParseFenError::InvalidChar('Q')));
+    assert!(parse("4/4/W13/4 w 4").is_err_and(|e| e == ParseFenError::RankLength(2, 14, 4)));
+    assert!(parse("4/4/99999999999999999999999/4 w 4")
+        .is_err_and(|e| e == ParseFenError::RankLength(2, 99, 4)));
+}
@@ -1,82 +1,269 @@
 use super::*;
+use std::{error, fmt};
 
 pub type BStrat = Box<dyn Strategy>;
 
 pub struct Game {
+    start: GameState,
     state: GameState,
     log: Vec<Move>,
-    enable_log: bool,
+    /// the winner and how the game ended, if it ended before the board was full
+    forfeit_winner: Option<(Player, Termination)>,
     white: BStrat,
     black: BStrat,
 }
 
 impl Game {
     pub fn new(white: BStrat, black: BStrat) -> Self {
+        Self::from_state(GameState::new(), white, black)
+    }
+    /// Starts a game from an arbitrary position, e.g. a smaller board or a different layout.
+    pub fn from_state(state: GameState, white: BStrat, black: BStrat) -> Self {
         Self {
-            state: GameState::new(),
+            start: state.clone(),
+            state,
             white,
             black,
             log: Vec::new(),
-            enable_log: false,
+            forfeit_winner: None,
         }
     }
-    pub fn enable_log(&mut self) {
-        self.enable_log = true;
-    }
+    /// does nothing, the moves are always logged for undoing and for game records
+    #[deprecated(note = "the moves are always logged")]
+    pub fn enable_log(&mut self) {}
+    /// the moves played so far
     pub fn log(&self) -> &[Move] {
         &self.log
     }
     #[inline]
     fn do_move(&mut self, mov: Move) {
         self.state.do_move(mov);
-        if self.enable_log {
-            self.log.push(mov);
+        self.log.push(mov);
+    }
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+    /// the winner and how the game ended, if it is over
+    pub fn outcome(&self) -> Option<(Player, Termination)> {
+        self.forfeit_winner.or_else(|| {
+            self.state
+                .is_finished()
+                .then(|| (self.state.turn().enemy(), Termination::Normal))
+        })
+    }
+    /// ends the game with a loss for `loser`
+    pub fn forfeit(&mut self, loser: Player, reason: Termination) {
+        self.forfeit_winner = Some((loser.enemy(), reason));
+    }
+    /// a record of the game so far, with today's date
+    pub fn record(&self) -> GameRecord {
+        let (winner, termination) = match self.outcome() {
+            Some((winner, termination)) => (Some(winner), termination),
+            None => (None, Termination::Unterminated),
+        };
+        GameRecord {
+            white: self.white.name(),
+            black: self.black.name(),
+            date: record::today(),
+            winner,
+            termination,
+            start: self.start.clone(),
+            moves: self.log.clone(),
+            extra: Vec::new(),
         }
     }
-    pub fn next_move(&mut self) -> Move {
-        let player = match self.state.turn {
+    /// takes back up to `plies` moves, and a resignation or forfeit with them
+    pub fn undo(&mut self, plies: usize) {
+        for _ in 0..plies {
+            match self.log.pop() {
+                Some(mov) => self.state.undo_move(mov),
+                None => break,
+            }
+        }
+        self.forfeit_winner = None;
+    }
+    /// lets the player to move act, which is usually playing a move,
+    /// an illegal move or a failing strategy leaves the game as it was
+    pub fn next_move(&mut self) -> Result<Action, MoveError> {
+        let player = match self.state.turn() {
             Player::White => &mut self.white,
             Player::Black => &mut self.black,
         };
-        let mov = player.find_move(&self.state);
-        if self.state.legal_move(mov) {
-            self.do_move(mov);
-            mov
-        } else {
-            eprintln!("{}", self.state.board.display(&BoardStyle::default()));
-            panic!(
-                "Player {:?} with strategy '{}' tried to do the illegal move {}.",
-                self.state.turn,
-                player.name(),
-                mov
-            );
+        let state = &self.state;
+        let played = self.log.len();
+        let result =
+            player
+                .act(state)
+                .map_err(MoveFailure::Strategy)
+                .and_then(|action| match action {
+                    Action::Move(mov) => match state.check_move(mov) {
+                        Ok(()) => Ok(action),
+                        Err(reason) => Err(MoveFailure::Illegal(mov, reason)),
+                    },
+                    // the player's own move and the opponent's reply are taken back
+                    Action::Undo if played < 2 => Err(MoveFailure::NothingToUndo),
+                    _ => Ok(action),
+                });
+        match result {
+            Ok(action) => {
+                match action {
+                    Action::Move(mov) => self.do_move(mov),
+                    Action::Resign => self.forfeit(self.state.turn(), Termination::Resignation),
+                    Action::Undo => self.undo(2),
+                }
+                Ok(action)
+            }
+            Err(failure) => Err(MoveError {
+                player: self.state.turn(),
+                strategy: player.name(),
+                failure,
+                position: self.state.to_fen(),
+            }),
         }
     }
     /// plays the current game to the end and returns the winner
-    pub fn finish(&mut self) -> Player {
-        while !self.state.is_finished() {
-            self.next_move();
+    pub fn finish(&mut self) -> Result<Player, MoveError> {
+        loop {
+            if let Some((winner, _)) = self.outcome() {
+                return Ok(winner);
+            }
+            self.next_move()?;
         }
-        self.state.turn.enemy()
     }
 
-    /// an iterator representing the moves of the remaining game
-    pub fn as_iter(&mut self) -> GameIter {
-        GameIter { game: self }
+    /// an iterator representing the moves of the remaining game,
+    /// which ends after the first strategy that fails to move
+    pub fn as_iter(&mut self) -> GameIter<'_> {
+        GameIter {
+            game: self,
+            failed: false,
+        }
     }
 }
 
 pub struct GameIter<'a> {
     game: &'a mut Game,
+    failed: bool,
 }
 
 impl Iterator for GameIter<'_> {
-    type Item = Move;
-    fn next(&mut self) -> Option<Move> {
-        if self.game.state.is_finished() {
-            None
-        } else {
-            Some(self.game.next_move())
+    type Item = Result<Move, MoveError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed && self.game.outcome().is_none() {
+            match self.game.next_move() {
+                Ok(Action::Move(mov)) => return Some(Ok(mov)),
+                Ok(_) => {}
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A strategy didn't come up with a legal move, see [`Game::next_move`].
+#[derive(Clone, Debug)]
+pub struct MoveError {
+    pub player: Player,
+    pub strategy: String,
+    pub failure: MoveFailure,
+    /// The position the move was tried in, in the notation of [`GameState::to_fen`].
+    pub position: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveFailure {
+    Illegal(Move, IllegalMove),
+    Strategy(StrategyError),
+    /// The player wants to undo before having moved.
+    NothingToUndo,
+}
+
+impl MoveError {
+    /// How the game ends if the player forfeits because of this error.
+    pub fn termination(&self) -> Termination {
+        match self.failure {
+            MoveFailure::Illegal(..) | MoveFailure::NothingToUndo => Termination::IllegalMove,
+            MoveFailure::Strategy(StrategyError::Timeout(_)) => Termination::TimeForfeit,
+            MoveFailure::Strategy(_) => Termination::StrategyFailure,
+        }
+    }
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.failure {
+            MoveFailure::Illegal(mov, reason) => write!(
+                f,
+                "Player {:?} with strategy '{}' tried to do the illegal move {}: {}.",
+                self.player, self.strategy, mov, reason
+            )?,
+            MoveFailure::Strategy(err) => write!(
+                f,
+                "Player {:?} with strategy '{}' failed to find a move: {}.",
+                self.player, self.strategy, err
+            )?,
+            MoveFailure::NothingToUndo => write!(
+                f,
+                "Player {:?} with strategy '{}' has no move to take back.",
+                self.player, self.strategy
+            )?,
+        }
+        write!(f, " Position: {}", self.position)
+    }
+}
+
+impl error::Error for MoveError {}
+
+#[test]
+fn game_iter_stops_on_errors() {
+    #[derive(Clone)]
+    struct Illegal;
+    impl Strategy for Illegal {
+        fn name(&self) -> String {
+            "Illegal".into()
+        }
+        fn find_move(&mut self, _: &GameState) -> Move {
+            "a0/a1/a2".parse().unwrap()
+        }
+        fn dup(&self) -> Box<dyn Strategy> {
+            Box::new(self.clone())
         }
     }
+
+    let mut game = Game::new(Box::new(strat::Snail), Box::new(Illegal));
+    let mut moves = game.as_iter();
+    assert!(moves.next().unwrap().is_ok());
+    let err = moves.next().unwrap().unwrap_err();
+    assert_eq!(err.player, Player::Black);
+    assert!(matches!(err.failure, MoveFailure::Illegal(..)));
+    assert!(moves.next().is_none());
+    assert_eq!(game.log().len(), 1);
+}
+
+#[test]
+fn undo_needs_a_move() {
+    struct Undo;
+    impl Strategy for Undo {
+        fn name(&self) -> String {
+            "Undo".into()
+        }
+        fn find_move(&mut self, _: &GameState) -> Move {
+            unreachable!()
+        }
+        fn act(&mut self, _: &GameState) -> Result<Action, StrategyError> {
+            Ok(Action::Undo)
+        }
+        fn dup(&self) -> Box<dyn Strategy> {
+            Box::new(Undo)
+        }
+    }
+
+    let mut game = Game::new(Box::new(strat::Snail), Box::new(Undo));
+    game.next_move().unwrap();
+    let err = game.next_move().unwrap_err();
+    assert_eq!(err.failure, MoveFailure::NothingToUndo);
+    assert_eq!(game.log().len(), 1);
 }
@@ -1,10 +1,31 @@
+pub mod bitboard;
 pub mod board;
+pub mod fen;
+#[allow(clippy::module_inception)]
 pub mod game;
 pub mod r#move;
+pub mod movegen;
+pub mod perft;
 pub mod player;
 pub mod pos;
+pub mod record;
+pub mod sgf;
 pub mod show;
 pub mod state;
 pub mod strat;
+pub mod zobrist;
 
-pub use {board::*, game::*, player::*, pos::*, r#move::*, state::*, strat::Strategy};
+pub use {
+    bitboard::*,
+    board::*,
+    fen::*,
+    game::*,
+    movegen::*,
+    player::*,
+    pos::*,
+    r#move::*,
+    record::*,
+    sgf::*,
+    state::*,
+    strat::{Action, Strategy, StrategyError},
+};
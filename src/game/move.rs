@@ -1,7 +1,7 @@
 use super::*;
-use std::fmt;
+use std::{error, fmt, str::FromStr};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Move {
     pub from: Pos,
     pub to: Pos,
@@ -13,3 +13,109 @@ impl fmt::Display for Move {
         write!(f, "{}/{}/{}", self.from, self.to, self.arrow)
     }
 }
+
+impl Move {
+    /// Formats the move like `d1-d7/g7`, with 1-based ranks.
+    pub fn community_notation(&self) -> String {
+        format!(
+            "{}-{}/{}",
+            self.from.community_notation(),
+            self.to.community_notation(),
+            self.arrow.community_notation()
+        )
+    }
+}
+
+/// Parses either the notation of `Display` (`a3/a7/c5`, 0-based ranks)
+/// or the community notation (`d1-d7/g7`, 1-based ranks).
+/// The dash between the first two squares tells the two apart.
+impl FromStr for Move {
+    type Err = ParseMoveError;
+    fn from_str(s: &str) -> Result<Self, ParseMoveError> {
+        let s = s.trim();
+        let (squares, first_rank) = match s.split_once('-') {
+            Some((from, rest)) => match rest.split_once('/') {
+                Some((to, arrow)) => ([from, to, arrow], 1),
+                None => return Err(ParseMoveError::MissingArrow),
+            },
+            None => match s.split('/').collect::<Vec<_>>()[..] {
+                [from, to, arrow] => ([from, to, arrow], 0),
+                [_, _] => return Err(ParseMoveError::MissingArrow),
+                _ => return Err(ParseMoveError::Format(s.into())),
+            },
+        };
+        let parse = |i: usize| {
+            Pos::parse(squares[i], first_rank).map_err(|err| ParseMoveError::Square(i, err))
+        };
+        Ok(Self {
+            from: parse(0)?,
+            to: parse(1)?,
+            arrow: parse(2)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseMoveError {
+    Format(String),
+    MissingArrow,
+    /// The square with the given index (0: from, 1: to, 2: arrow) is invalid.
+    Square(usize, ParsePosError),
+}
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Format(s) => write!(
+                f,
+                "'{}' is neither of the form 'a3/a7/c5' nor 'd1-d7/g7'",
+                s
+            ),
+            Self::MissingArrow => write!(f, "the arrow square is missing"),
+            Self::Square(i, err) => {
+                let square = ["origin", "destination", "arrow"][*i];
+                write!(f, "invalid {} square: {}", square, err)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseMoveError {}
+
+#[test]
+fn parse_moves() {
+    let mov = Move {
+        from: (3, 0).into(),
+        to: (3, 6).into(),
+        arrow: (6, 6).into(),
+    };
+    assert_eq!("d0/d6/g6".parse(), Ok(mov));
+    assert_eq!("D1-D7/G7".parse(), Ok(mov));
+    assert_eq!(mov.to_string().parse(), Ok(mov));
+    assert_eq!(mov.community_notation().parse(), Ok(mov));
+    assert_eq!("j10-j1/a1".parse::<Move>().unwrap().from, (9, 9).into());
+
+    assert_eq!("d1-d7".parse::<Move>(), Err(ParseMoveError::MissingArrow));
+    assert_eq!(
+        "d1-d0/g7".parse::<Move>(),
+        Err(ParseMoveError::Square(
+            1,
+            ParsePosError::InvalidRank("0".into())
+        ))
+    );
+    assert_eq!(
+        "d0/#6/g6".parse::<Move>(),
+        Err(ParseMoveError::Square(1, ParsePosError::InvalidFile('#')))
+    );
+    assert_eq!(
+        "d0/d6/z6".parse::<Move>(),
+        Err(ParseMoveError::Square(
+            2,
+            ParsePosError::TooLarge("z6".into())
+        ))
+    );
+    assert!(matches!(
+        "d0 d6 g6".parse::<Move>(),
+        Err(ParseMoveError::Format(_))
+    ));
+}
@@ -0,0 +1,104 @@
+use super::*;
+
+/// Iterator over all legal moves of a position, see [`GameState::moves`].
+///
+/// Amazons are visited square by square, then every destination and for each
+/// destination every arrow square. Arrows are generated with the moving amazon
+/// lifted off the board, so they can be shot over or onto the square it came from.
+pub struct MoveIter {
+    empty: Bitboard,
+    amazons: Bitboard,
+    targets: Bitboard,
+    arrows: Bitboard,
+    from: Pos,
+    to: Pos,
+}
+
+impl MoveIter {
+    pub fn new(state: &GameState) -> Self {
+        let origin = (0, 0).into();
+        Self {
+            empty: state.board().empty(),
+            amazons: state.board().amazons(state.turn()),
+            targets: Bitboard::EMPTY,
+            arrows: Bitboard::EMPTY,
+            from: origin,
+            to: origin,
+        }
+    }
+}
+
+impl Iterator for MoveIter {
+    type Item = Move;
+    #[inline]
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            if let Some(arrow) = self.arrows.first() {
+                self.arrows ^= Bitboard::square(arrow);
+                return Some(Move {
+                    from: self.from,
+                    to: self.to,
+                    arrow,
+                });
+            } else if let Some(to) = self.targets.first() {
+                self.targets ^= Bitboard::square(to);
+                self.to = to;
+                self.arrows =
+                    Bitboard::square(to).queen_moves(self.empty | Bitboard::square(self.from));
+            } else if let Some(from) = self.amazons.first() {
+                self.amazons ^= Bitboard::square(from);
+                self.from = from;
+                self.targets = Bitboard::square(from).queen_moves(self.empty);
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+#[test]
+fn start_position_move_count() {
+    assert_eq!(GameState::new().moves().count(), 2176);
+}
+
+#[cfg(test)]
+fn check_against_legal_move(mut state: GameState, plies: usize) {
+    use rand::prelude::*;
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..plies {
+        let moves = state.legal_moves();
+
+        let mut expected = vec![];
+        for from in state.find_amazons() {
+            for to in state.board().squares() {
+                for arrow in state.board().squares() {
+                    let mov = Move { from, to, arrow };
+                    if state.legal_move(mov) {
+                        expected.push(mov);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(moves.len(), expected.len());
+        assert!(expected.iter().all(|m| moves.contains(m)));
+
+        if moves.is_empty() {
+            break;
+        }
+        state.do_move(moves[rng.gen_range(0..moves.len())]);
+    }
+}
+
+#[test]
+fn moves_match_legal_move() {
+    check_against_legal_move(GameState::new(), 30);
+}
+
+#[test]
+fn moves_match_legal_move_on_all_sizes() {
+    for len in 4..=MAX_BOARD_LEN {
+        check_against_legal_move(GameState::with_size(len), 4);
+    }
+}
@@ -0,0 +1,103 @@
+//! Move path enumeration, used to check the move generator against known counts.
+
+use super::*;
+
+/// Counts the legal move sequences of length `depth` starting at `state`.
+pub fn perft(state: &mut GameState, depth: usize) -> u64 {
+    match depth {
+        0 => 1,
+        1 => state.moves().count() as u64,
+        _ => {
+            let mut count = 0;
+            for mov in state.moves() {
+                state.do_move(mov);
+                count += perft(state, depth - 1);
+                state.undo_move(mov);
+            }
+            count
+        }
+    }
+}
+
+/// Like [`perft`], but lists the count for each first move separately.
+pub fn divide(state: &mut GameState, depth: usize) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    state
+        .legal_moves()
+        .into_iter()
+        .map(|mov| {
+            state.do_move(mov);
+            let count = perft(state, depth - 1);
+            state.undo_move(mov);
+            (mov, count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn walled_in(white: &[Pos], black: &[Pos], empty: &[Pos]) -> GameState {
+    let mut board = Board::new(DEFAULT_BOARD_LEN);
+    for x in 0..board.size() as u8 {
+        for y in 0..board.size() as u8 {
+            board.set((x, y), Slot::Arrow);
+        }
+    }
+    for &p in empty {
+        board.set(p, Slot::Empty);
+    }
+    for &p in white {
+        board.set(p, Slot::Amazon(Player::White));
+    }
+    for &p in black {
+        board.set(p, Slot::Amazon(Player::Black));
+    }
+    GameState::from_board(board, Player::White)
+}
+
+#[test]
+fn perft_start_position() {
+    let mut state = GameState::new();
+    assert_eq!(perft(&mut state, 0), 1);
+    assert_eq!(perft(&mut state, 1), 2176);
+    assert_eq!(perft(&mut state, 2), 4_307_152);
+    assert!(state == GameState::new());
+}
+
+#[test]
+fn perft_corridor() {
+    let mut state = walled_in(
+        &[(0, 0).into()],
+        &[(9, 9).into()],
+        &[(1, 0).into(), (2, 0).into(), (8, 9).into()],
+    );
+    assert_eq!(perft(&mut state, 1), 4);
+    assert_eq!(perft(&mut state, 2), 4);
+    assert_eq!(perft(&mut state, 3), 3);
+}
+
+#[test]
+fn perft_open_square() {
+    let empty = (0..3)
+        .flat_map(|x| (0..3).map(move |y| (x, y).into()))
+        .chain(Some((8, 9).into()))
+        .collect::<Vec<_>>();
+    let mut state = walled_in(&[(1, 1).into()], &[(9, 9).into()], &empty);
+    assert_eq!(perft(&mut state, 1), 48);
+    assert_eq!(perft(&mut state, 2), 48);
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let mut state = GameState::with_size(6);
+    let mov = state.moves().next().unwrap();
+    state.do_move(mov);
+
+    let divided = divide(&mut state, 2);
+    assert_eq!(divided.len() as u64, perft(&mut state, 1));
+    assert_eq!(
+        divided.iter().map(|(_, n)| n).sum::<u64>(),
+        perft(&mut state, 2)
+    );
+}
@@ -1,7 +1,19 @@
 use super::*;
-use std::fmt;
+use std::{error, fmt, str::FromStr};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// The eight queen directions.
+pub const DIRECTIONS: [(i8, i8); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Pos {
     pub x: u8,
     pub y: u8,
@@ -44,18 +56,10 @@ impl Pos {
         }
     }
 
+    /// Returns the surrounding positions on a board of side length `len`.
     #[inline]
-    pub fn neighbors(self) -> Vec<Self> {
-        let mut res = Vec::with_capacity(8);
-        for x in self.x.saturating_sub(1)..(self.x + 2).min(BOARD_LEN as u8) {
-            for y in self.y.saturating_sub(1)..(self.y + 2).min(BOARD_LEN as u8) {
-                let p = (x, y).into();
-                if p != self {
-                    res.push(p);
-                }
-            }
-        }
-        res
+    pub fn neighbors(self, len: usize) -> Bitboard {
+        Bitboard::square(self).king_moves() & Bitboard::board(len)
     }
 }
 
@@ -65,6 +69,73 @@ impl fmt::Display for Pos {
     }
 }
 
+impl Pos {
+    /// Formats the position with 1-based ranks, as is common outside of this crate.
+    pub fn community_notation(self) -> String {
+        format!("{}{}", (self.x + 0x61u8) as char, self.y + 1)
+    }
+
+    /// Parses a file letter followed by a rank number, where `first_rank` is the number of rank 0.
+    pub fn parse(s: &str, first_rank: u8) -> Result<Self, ParsePosError> {
+        let s = s.trim();
+        let mut chars = s.chars();
+        let file = chars.next().ok_or(ParsePosError::Empty)?;
+        let x = match file.to_ascii_lowercase() {
+            f @ 'a'..='z' => f as u8 - b'a',
+            _ => return Err(ParsePosError::InvalidFile(file)),
+        };
+        let rank = chars.as_str();
+        if rank.is_empty() {
+            return Err(ParsePosError::MissingRank);
+        }
+        let y = rank
+            .parse::<u8>()
+            .ok()
+            .filter(|_| rank.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|r| r.checked_sub(first_rank))
+            .ok_or_else(|| ParsePosError::InvalidRank(rank.into()))?;
+        if x as usize >= MAX_BOARD_LEN || y as usize >= MAX_BOARD_LEN {
+            return Err(ParsePosError::TooLarge(s.into()));
+        }
+        Ok((x, y).into())
+    }
+}
+
+/// Parses the notation of `Display`, with 0-based ranks. Files may be upper case.
+impl FromStr for Pos {
+    type Err = ParsePosError;
+    fn from_str(s: &str) -> Result<Self, ParsePosError> {
+        Self::parse(s, 0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParsePosError {
+    Empty,
+    InvalidFile(char),
+    MissingRank,
+    InvalidRank(String),
+    TooLarge(String),
+}
+
+impl fmt::Display for ParsePosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "expected a square, found nothing"),
+            Self::InvalidFile(c) => write!(f, "'{}' is not a file letter", c),
+            Self::MissingRank => write!(f, "the rank number is missing"),
+            Self::InvalidRank(r) => write!(f, "'{}' is not a valid rank", r),
+            Self::TooLarge(s) => write!(
+                f,
+                "{} is outside of the largest supported board ({}x{})",
+                s, MAX_BOARD_LEN, MAX_BOARD_LEN
+            ),
+        }
+    }
+}
+
+impl error::Error for ParsePosError {}
+
 impl From<(u8, u8)> for Pos {
     fn from((x, y): (u8, u8)) -> Self {
         Self { x, y }
@@ -0,0 +1,411 @@
+//! A PGN-like text format for finished and unfinished games.
+//!
+//! A record is a block of `[Name "value"]` headers followed by the numbered move list
+//! and the result:
+//!
+//! ```text
+//! [White "LookAhead"]
+//! [Black "Minimax(2)"]
+//! [Date "2022.05.14"]
+//! [Result "1-0"]
+//! [Termination "normal"]
+//! [Size "10"]
+//! [Position "3B2B3/10/10/B8B/10/10/W8W/10/10/3W2W3 w 10"]
+//!
+//! 1. a6/a5/a6 d0/d5/i5 2. ... 1-0
+//! ```
+//!
+//! Moves are written in the notation of `Move`'s `Display`, but the community notation is read as well.
+//! Several records can be concatenated into one file, see [`GameRecord::parse_all`].
+
+use super::*;
+use std::{error, fmt, str::FromStr, time::SystemTime};
+
+#[derive(Clone)]
+pub struct GameRecord {
+    pub white: String,
+    pub black: String,
+    /// The date in `YYYY.MM.DD` format
+    pub date: String,
+    /// `None` if the game is not over
+    pub winner: Option<Player>,
+    pub termination: Termination,
+    pub start: GameState,
+    pub moves: Vec<Move>,
+    /// Headers that have no field of their own, in the order they were read
+    pub extra: Vec<(String, String)>,
+}
+
+/// How a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// The loser had no moves left.
+    Normal,
+    /// The loser tried an illegal move.
+    IllegalMove,
+    /// The loser ran out of time.
+    TimeForfeit,
+    /// The loser gave up.
+    Resignation,
+    /// The loser's strategy failed, e.g. because an external engine crashed.
+    StrategyFailure,
+    /// The game isn't over.
+    Unterminated,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "normal",
+            Self::IllegalMove => "illegal move",
+            Self::TimeForfeit => "time forfeit",
+            Self::Resignation => "resignation",
+            Self::StrategyFailure => "strategy failure",
+            Self::Unterminated => "unterminated",
+        })
+    }
+}
+
+impl FromStr for Termination {
+    type Err = ParseRecordError;
+    fn from_str(s: &str) -> Result<Self, ParseRecordError> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "illegal move" => Ok(Self::IllegalMove),
+            "time forfeit" => Ok(Self::TimeForfeit),
+            "resignation" => Ok(Self::Resignation),
+            "strategy failure" => Ok(Self::StrategyFailure),
+            "unterminated" => Ok(Self::Unterminated),
+            _ => Err(ParseRecordError::InvalidHeader("Termination", s.into())),
+        }
+    }
+}
+
+pub(crate) fn result_str(winner: Option<Player>) -> &'static str {
+    match winner {
+        Some(Player::White) => "1-0",
+        Some(Player::Black) => "0-1",
+        None => "*",
+    }
+}
+
+fn parse_result(s: &str) -> Option<Option<Player>> {
+    match s {
+        "1-0" => Some(Some(Player::White)),
+        "0-1" => Some(Some(Player::Black)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+impl GameRecord {
+    /// Plays the moves from the starting position and returns the final position,
+    /// or the index of the first illegal move and why it is illegal.
+    pub fn replay(&self) -> Result<GameState, (usize, IllegalMove)> {
+        let mut state = self.start.clone();
+        for (i, &mov) in self.moves.iter().enumerate() {
+            state.try_do_move(mov).map_err(|err| (i, err))?;
+        }
+        Ok(state)
+    }
+
+    /// Reads all records of a text containing several of them one after another.
+    pub fn parse_all(s: &str) -> Result<Vec<Self>, ParseRecordError> {
+        let mut records = Vec::new();
+        let mut current = String::new();
+        let mut in_moves = false;
+        for line in s.lines() {
+            let is_header = line.trim_start().starts_with('[');
+            if is_header && in_moves {
+                records.push(current.parse()?);
+                current.clear();
+                in_moves = false;
+            }
+            in_moves |= !is_header && !line.trim().is_empty();
+            current += line;
+            current += "\n";
+        }
+        if !current.trim().is_empty() {
+            records.push(current.parse()?);
+        }
+        Ok(records)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn header(f: &mut fmt::Formatter, name: &str, value: &str) -> fmt::Result {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", name, value)
+        }
+        header(f, "White", &self.white)?;
+        header(f, "Black", &self.black)?;
+        header(f, "Date", &self.date)?;
+        header(f, "Result", result_str(self.winner))?;
+        header(f, "Termination", &self.termination.to_string())?;
+        header(f, "Size", &self.start.board().size().to_string())?;
+        header(f, "Position", &self.start.to_fen())?;
+        for (name, value) in &self.extra {
+            header(f, name, value)?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = Vec::with_capacity(self.moves.len() * 3 / 2 + 2);
+        let mut turn = self.start.turn();
+        for (i, mov) in self.moves.iter().enumerate() {
+            let number = (i + (self.start.turn() == Player::Black) as usize) / 2 + 1;
+            if turn == Player::White {
+                tokens.push(format!("{}.", number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", number));
+            }
+            tokens.push(mov.to_string());
+            turn = turn.enemy();
+        }
+        tokens.push(result_str(self.winner).into());
+
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + token.len() >= 80 {
+                writeln!(f)?;
+                line_len = 0;
+            } else if line_len > 0 {
+                write!(f, " ")?;
+                line_len += 1;
+            }
+            write!(f, "{}", token)?;
+            line_len += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = ParseRecordError;
+    fn from_str(s: &str) -> Result<Self, ParseRecordError> {
+        let mut headers = Vec::new();
+        let mut movetext = String::new();
+        for line in s.lines().map(str::trim) {
+            if line.starts_with('[') && movetext.is_empty() {
+                headers.push(parse_header(line)?);
+            } else if !line.is_empty() {
+                movetext += line;
+                movetext += " ";
+            }
+        }
+
+        let mut record = GameRecord {
+            white: String::new(),
+            black: String::new(),
+            date: String::new(),
+            winner: None,
+            termination: Termination::Unterminated,
+            start: GameState::new(),
+            moves: Vec::new(),
+            extra: Vec::new(),
+        };
+        let mut size = None;
+        let mut position = None;
+        let mut result = None;
+        for (name, value) in headers {
+            match name.as_str() {
+                "White" => record.white = value,
+                "Black" => record.black = value,
+                "Date" => record.date = value,
+                "Result" => {
+                    result = Some(
+                        parse_result(&value)
+                            .ok_or(ParseRecordError::InvalidHeader("Result", value))?,
+                    )
+                }
+                "Termination" => record.termination = value.parse()?,
+                "Size" => {
+                    size = Some(
+                        value
+                            .parse::<usize>()
+                            .map_err(|_| ParseRecordError::InvalidHeader("Size", value))?,
+                    )
+                }
+                "Position" => position = Some(GameState::from_fen(&value)?),
+                _ => record.extra.push((name, value)),
+            }
+        }
+        record.start = match (position, size) {
+            (Some(start), Some(len)) if start.board().size() != len => {
+                return Err(ParseRecordError::SizeMismatch(len, start.board().size()))
+            }
+            (Some(start), _) => start,
+            // without a position the standard layout is scaled, which needs at least 4x4
+            (None, Some(len)) if !(4..=MAX_BOARD_LEN).contains(&len) => {
+                return Err(ParseRecordError::InvalidHeader("Size", len.to_string()))
+            }
+            (None, Some(len)) => GameState::with_size(len),
+            (None, None) => GameState::new(),
+        };
+
+        let mut tokens = movetext.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            if let Some(winner) = parse_result(token) {
+                if tokens.peek().is_some() {
+                    return Err(ParseRecordError::TrailingInput(tokens.collect()));
+                }
+                if result.is_some_and(|result| result != winner) {
+                    return Err(ParseRecordError::ResultMismatch);
+                }
+                result = Some(winner);
+            } else if !is_move_number(token) {
+                let mov = token
+                    .parse()
+                    .map_err(|err| ParseRecordError::Move(token.into(), err))?;
+                record.moves.push(mov);
+            }
+        }
+        record.winner = result.flatten();
+
+        Ok(record)
+    }
+}
+
+fn is_move_number(token: &str) -> bool {
+    let digits = token.trim_end_matches('.');
+    digits.len() < token.len() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn parse_header(line: &str) -> Result<(String, String), ParseRecordError> {
+    let invalid = || ParseRecordError::InvalidHeaderLine(line.into());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' {
+            chars.next().ok_or_else(invalid)?
+        } else {
+            c
+        });
+    }
+    Ok((name.into(), unescaped))
+}
+
+/// Today's date in `YYYY.MM.DD` format (UTC).
+pub(crate) fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    date(secs)
+}
+
+/// The date `secs` seconds after 1970-01-01 in `YYYY.MM.DD` format (UTC).
+fn date(secs: u64) -> String {
+    // converts days since 1970-01-01 to a date in the proleptic Gregorian calendar
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseRecordError {
+    InvalidHeaderLine(String),
+    InvalidHeader(&'static str, String),
+    Position(ParseFenError),
+    /// The `Size` header and the size of the `Position` header differ.
+    SizeMismatch(usize, usize),
+    Move(String, ParseMoveError),
+    /// The result after the moves differs from the `Result` header.
+    ResultMismatch,
+    TrailingInput(String),
+}
+
+impl From<ParseFenError> for ParseRecordError {
+    fn from(err: ParseFenError) -> Self {
+        Self::Position(err)
+    }
+}
+
+impl fmt::Display for ParseRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHeaderLine(line) => write!(f, "'{}' is not a valid header", line),
+            Self::InvalidHeader(name, value) => {
+                write!(f, "'{}' is not a valid value for {}", value, name)
+            }
+            Self::Position(err) => write!(f, "invalid position: {}", err),
+            Self::SizeMismatch(size, fen) => {
+                write!(f, "the size is {} but the position has size {}", size, fen)
+            }
+            Self::Move(token, err) => write!(f, "invalid move '{}': {}", token, err),
+            Self::ResultMismatch => write!(f, "the result doesn't match the Result header"),
+            Self::TrailingInput(s) => write!(f, "unexpected '{}' after the result", s),
+        }
+    }
+}
+
+impl error::Error for ParseRecordError {}
+
+#[test]
+fn record_round_trip() {
+    let mut game = Game::new(Box::new(strat::Random), Box::new(strat::Snail));
+    let winner = game.finish().unwrap();
+    let mut record = game.record();
+    record.white = "Name with \"quotes\" and \\".into();
+    record.extra.push(("Event".into(), "Test".into()));
+
+    let text = record.to_string();
+    let read = text.parse::<GameRecord>().unwrap();
+    assert_eq!(read.white, record.white);
+    assert_eq!(read.black, "Snail");
+    assert_eq!(read.date, record.date);
+    assert_eq!(read.winner, Some(winner));
+    assert_eq!(read.termination, Termination::Normal);
+    assert!(read.start == GameState::new());
+    assert_eq!(read.moves, game.log());
+    assert_eq!(read.extra, record.extra);
+    assert!(read.replay().unwrap() == *game.state());
+
+    let both = format!("{}\n{}", text, text);
+    assert_eq!(GameRecord::parse_all(&both).unwrap().len(), 2);
+}
+
+#[test]
+fn record_from_black_to_move() {
+    let text = "[Position \"4/B2W/4/4 b 4\"]\n\n1... a2-a1/a2 2. d2-c2/d2 *\n";
+    let record = text.parse::<GameRecord>().unwrap();
+    assert_eq!(record.winner, None);
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(record.moves[0], "a1/a0/a1".parse().unwrap());
+    assert!(record.replay().is_ok());
+    assert!(record
+        .to_string()
+        .ends_with("\n1... a1/a0/a1 2. d1/c1/d1 *\n"));
+}
+
+#[test]
+fn dates() {
+    assert_eq!(date(0), "1970.01.01");
+    assert_eq!(date(1704067199), "2023.12.31");
+    assert_eq!(date(1704067200), "2024.01.01");
+    assert_eq!(date(1709164800 - 1), "2024.02.28");
+    assert_eq!(date(1709164800), "2024.02.29");
+    assert_eq!(date(1709164800 + 86400), "2024.03.01");
+    assert_eq!(date(951782400), "2000.02.29");
+    // 2100 is not a leap year
+    assert_eq!(date(4107542400 - 86400), "2100.02.28");
+    assert_eq!(date(4107542400), "2100.03.01");
+}
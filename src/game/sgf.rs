@@ -0,0 +1,550 @@
+//! Reading and writing games in the Smart Game Format (SGF), game type 18.
+//!
+//! Points are written as two letters, file and rank, with `aa` being the square `a0`.
+//! A move node holds the amazon move as `W[<from><to>]` or `B[<from><to>]`
+//! and the arrow as `AA[<arrow>]`. A third point in the move value is read as the arrow as well.
+//! Positions other than the standard layout are described in the root node by the setup
+//! properties `AW`, `AB` (amazons), `AA` (arrows) and `PL` (player to move).
+//! A game lost because a strategy failed has the result `W+Failure` or `B+Failure`,
+//! as SGF has no standard result for it.
+
+use super::*;
+use std::{error, fmt};
+
+/// A tree of moves. The first child of each node continues the main line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveTree {
+    pub children: Vec<(Move, MoveTree)>,
+}
+
+impl MoveTree {
+    /// A tree without variations.
+    pub fn line(moves: &[Move]) -> Self {
+        let mut tree = Self::default();
+        for &mov in moves.iter().rev() {
+            tree = Self {
+                children: vec![(mov, tree)],
+            };
+        }
+        tree
+    }
+
+    /// The moves along the first child of each node.
+    pub fn main_line(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let mut tree = self;
+        while let Some((mov, next)) = tree.children.first() {
+            moves.push(*mov);
+            tree = next;
+        }
+        moves
+    }
+}
+
+/// A node of a generic SGF game tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SgfNode {
+    pub properties: Vec<(String, Vec<String>)>,
+    pub children: Vec<SgfNode>,
+}
+
+impl SgfNode {
+    pub fn get(&self, ident: &str) -> Option<&[String]> {
+        self.properties
+            .iter()
+            .find(|(id, _)| id == ident)
+            .map(|(_, values)| &values[..])
+    }
+
+    fn first(&self, ident: &str) -> Option<&str> {
+        self.get(ident)
+            .and_then(|values| values.first())
+            .map(String::as_str)
+    }
+
+    fn add(&mut self, ident: &str, values: Vec<String>) {
+        self.properties.push((ident.into(), values));
+    }
+}
+
+/// Parses a collection of SGF game trees, each given by its root node.
+pub fn parse_collection(s: &str) -> Result<Vec<SgfNode>, SgfError> {
+    let mut parser = Parser {
+        s: s.as_bytes(),
+        pos: 0,
+    };
+    let mut trees = Vec::new();
+    parser.skip_whitespace();
+    while parser.pos < parser.s.len() {
+        trees.push(parser.game_tree()?);
+        parser.skip_whitespace();
+    }
+    if trees.is_empty() {
+        return Err(SgfError::Syntax(0, "expected '('"));
+    }
+    Ok(trees)
+}
+
+/// Writes a collection of SGF game trees, each given by its root node.
+pub fn write_collection(trees: &[SgfNode]) -> String {
+    fn write_sequence(buf: &mut String, node: &SgfNode) {
+        buf.push(';');
+        for (ident, values) in &node.properties {
+            buf.push_str(ident);
+            for value in values {
+                buf.push('[');
+                buf.push_str(&value.replace('\\', "\\\\").replace(']', "\\]"));
+                buf.push(']');
+            }
+        }
+        match &node.children[..] {
+            [] => {}
+            [child] => write_sequence(buf, child),
+            children => {
+                for child in children {
+                    buf.push('(');
+                    write_sequence(buf, child);
+                    buf.push(')');
+                }
+            }
+        }
+    }
+    let mut buf = String::new();
+    for tree in trees {
+        buf.push('(');
+        write_sequence(&mut buf, tree);
+        buf.push_str(")\n");
+    }
+    buf
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.s.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8, msg: &'static str) -> Result<(), SgfError> {
+        self.skip_whitespace();
+        if self.s.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(SgfError::Syntax(self.pos, msg))
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.s.get(self.pos).copied()
+    }
+
+    fn game_tree(&mut self) -> Result<SgfNode, SgfError> {
+        self.expect(b'(', "expected '('")?;
+        let mut sequence = Vec::new();
+        while self.peek() == Some(b';') {
+            self.pos += 1;
+            sequence.push(self.node()?);
+        }
+        if sequence.is_empty() {
+            return Err(SgfError::Syntax(self.pos, "expected ';'"));
+        }
+        let mut children = Vec::new();
+        while self.peek() == Some(b'(') {
+            children.push(self.game_tree()?);
+        }
+        self.expect(b')', "expected ')'")?;
+
+        let mut node = sequence.pop().unwrap();
+        node.children = children;
+        while let Some(mut parent) = sequence.pop() {
+            parent.children = vec![node];
+            node = parent;
+        }
+        Ok(node)
+    }
+
+    fn node(&mut self) -> Result<SgfNode, SgfError> {
+        let mut node = SgfNode::default();
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            let mut ident = String::new();
+            while let Some(&c) = self.s.get(self.pos).filter(|c| c.is_ascii_alphabetic()) {
+                // lower case letters are allowed in identifiers by old versions of the format
+                if c.is_ascii_uppercase() {
+                    ident.push(c as char);
+                }
+                self.pos += 1;
+            }
+            let mut values = Vec::new();
+            while self.peek() == Some(b'[') {
+                self.pos += 1;
+                values.push(self.value()?);
+            }
+            if values.is_empty() {
+                return Err(SgfError::Syntax(self.pos, "expected '['"));
+            }
+            node.add(&ident, values);
+        }
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<String, SgfError> {
+        let mut value = Vec::new();
+        loop {
+            match self.s.get(self.pos) {
+                None => return Err(SgfError::Syntax(self.pos, "unterminated property value")),
+                Some(b']') => break,
+                Some(b'\\') => {
+                    self.pos += 1;
+                    match self.s.get(self.pos) {
+                        // an escaped line break is removed
+                        Some(b'\n') => {}
+                        Some(&c) => value.push(c),
+                        None => continue,
+                    }
+                }
+                Some(&c) => value.push(c),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        Ok(String::from_utf8_lossy(&value).into_owned())
+    }
+}
+
+fn point(p: Pos) -> String {
+    [p.x, p.y].iter().map(|&c| (b'a' + c) as char).collect()
+}
+
+fn parse_points(s: &str) -> Option<Vec<Pos>> {
+    let bytes = s.as_bytes();
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    bytes
+        .chunks(2)
+        .map(|c| match c {
+            [x @ b'a'..=b'z', y @ b'a'..=b'z'] => Some((x - b'a', y - b'a').into()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn player_ident(player: Player) -> &'static str {
+    match player {
+        Player::White => "W",
+        Player::Black => "B",
+    }
+}
+
+impl GameRecord {
+    /// Writes the game as SGF, without variations.
+    pub fn to_sgf(&self) -> String {
+        self.to_sgf_tree(&MoveTree::line(&self.moves))
+    }
+
+    /// Writes the game as SGF, with the moves of `tree` instead of the record's moves.
+    pub fn to_sgf_tree(&self, tree: &MoveTree) -> String {
+        let len = self.start.board().size();
+        let mut root = SgfNode::default();
+        let single = |s: &str| vec![s.to_string()];
+        root.add("GM", single("18"));
+        root.add("FF", single("4"));
+        root.add("CA", single("UTF-8"));
+        root.add("SZ", single(&len.to_string()));
+        root.add("PW", single(&self.white));
+        root.add("PB", single(&self.black));
+        if !self.date.is_empty() {
+            root.add("DT", single(&self.date.replace('.', "-")));
+        }
+        if let Some(winner) = self.winner {
+            let reason = match self.termination {
+                Termination::IllegalMove => "F",
+                Termination::StrategyFailure => "Failure",
+                Termination::TimeForfeit => "T",
+                Termination::Resignation => "R",
+                _ => "",
+            };
+            root.add(
+                "RE",
+                single(&format!("{}+{}", player_ident(winner), reason)),
+            );
+        }
+        if len < 4 || self.start != GameState::with_size(len) {
+            let board = self.start.board();
+            let points = |set: Bitboard| set.iter().map(point).collect::<Vec<_>>();
+            root.add("AW", points(board.amazons(Player::White)));
+            root.add("AB", points(board.amazons(Player::Black)));
+            if !board.arrows().is_empty() {
+                root.add("AA", points(board.arrows()));
+            }
+            root.add("PL", single(player_ident(self.start.turn())));
+        }
+        for (name, value) in &self.extra {
+            if name.bytes().all(|b| b.is_ascii_uppercase()) {
+                root.add(name, single(value));
+            }
+        }
+
+        fn moves(tree: &MoveTree, turn: Player) -> Vec<SgfNode> {
+            tree.children
+                .iter()
+                .map(|(mov, next)| {
+                    let mut node = SgfNode::default();
+                    node.add(player_ident(turn), vec![point(mov.from) + &point(mov.to)]);
+                    node.add("AA", vec![point(mov.arrow)]);
+                    node.children = moves(next, turn.enemy());
+                    node
+                })
+                .collect()
+        }
+        root.children = moves(tree, self.start.turn());
+
+        write_collection(&[root])
+    }
+
+    /// Reads the first game of an SGF collection, following the main line.
+    pub fn from_sgf(s: &str) -> Result<Self, SgfError> {
+        let root = parse_collection(s)?.swap_remove(0);
+        Self::from_sgf_node(&root).map(|(record, _)| record)
+    }
+
+    /// Reads a game from the root node of an SGF game tree, with all of its variations.
+    /// The moves of the returned record are the main line of the tree.
+    pub fn from_sgf_node(root: &SgfNode) -> Result<(Self, MoveTree), SgfError> {
+        let invalid = |ident: &str, value: &str| SgfError::Property(ident.into(), value.into());
+
+        if let Some(gm) = root.first("GM") {
+            if gm.trim() != "18" {
+                return Err(SgfError::GameType(gm.into()));
+            }
+        }
+        let len = match root.first("SZ") {
+            Some(sz) => sz
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|len| (1..=MAX_BOARD_LEN).contains(len))
+                .ok_or_else(|| invalid("SZ", sz))?,
+            None => DEFAULT_BOARD_LEN,
+        };
+
+        let setup = ["AW", "AB", "AA", "PL"]
+            .iter()
+            .any(|ident| root.get(ident).is_some());
+        let start = if setup {
+            let mut board = Board::new(len);
+            for (ident, slot) in [
+                ("AW", Slot::Amazon(Player::White)),
+                ("AB", Slot::Amazon(Player::Black)),
+                ("AA", Slot::Arrow),
+            ] {
+                for value in root.get(ident).unwrap_or(&[]) {
+                    match parse_points(value).as_deref() {
+                        Some(&[p]) if board.contains(p) => board.set(p, slot),
+                        _ => return Err(invalid(ident, value)),
+                    }
+                }
+            }
+            let turn = match root.first("PL").map(str::trim) {
+                None | Some("W") => Player::White,
+                Some("B") => Player::Black,
+                Some(pl) => return Err(invalid("PL", pl)),
+            };
+            GameState::from_board(board, turn)
+        } else if len >= 4 {
+            GameState::with_size(len)
+        } else {
+            return Err(invalid("SZ", &len.to_string()));
+        };
+
+        let (winner, termination) = match root.first("RE").map(str::trim) {
+            Some(re) if re.starts_with("W+") || re.starts_with("B+") => {
+                let winner = if re.starts_with('W') {
+                    Player::White
+                } else {
+                    Player::Black
+                };
+                let termination = match &re[2..] {
+                    "F" | "Forfeit" => Termination::IllegalMove,
+                    "T" | "Time" => Termination::TimeForfeit,
+                    "R" | "Resign" => Termination::Resignation,
+                    "Failure" => Termination::StrategyFailure,
+                    _ => Termination::Normal,
+                };
+                (Some(winner), termination)
+            }
+            _ => (None, Termination::Unterminated),
+        };
+
+        let mut tree = MoveTree::default();
+        let mut state = start.clone();
+        read_moves(root, &mut state, &mut tree, 0)?;
+
+        let extra = root
+            .properties
+            .iter()
+            .filter(|(ident, _)| {
+                ![
+                    "GM", "FF", "CA", "AP", "SZ", "PW", "PB", "DT", "RE", "AW", "AB", "AA", "PL",
+                    "W", "B",
+                ]
+                .contains(&ident.as_str())
+            })
+            .filter_map(|(ident, values)| Some((ident.clone(), values.first()?.clone())))
+            .collect();
+
+        let record = GameRecord {
+            white: root.first("PW").unwrap_or_default().into(),
+            black: root.first("PB").unwrap_or_default().into(),
+            date: root.first("DT").unwrap_or_default().replace('-', "."),
+            winner,
+            termination,
+            start,
+            moves: tree.main_line(),
+            extra,
+        };
+        Ok((record, tree))
+    }
+}
+
+/// Adds the move of `node`, if any, and of all its descendants to `tree`.
+fn read_moves(
+    node: &SgfNode,
+    state: &mut GameState,
+    tree: &mut MoveTree,
+    ply: usize,
+) -> Result<(), SgfError> {
+    let mov = match (node.get("W"), node.get("B")) {
+        (Some(_), Some(_)) => return Err(SgfError::Property("W".into(), "B".into())),
+        (Some(value), None) => Some((Player::White, &value[0])),
+        (None, Some(value)) => Some((Player::Black, &value[0])),
+        (None, None) => None,
+    };
+
+    match mov {
+        Some((player, value)) => {
+            let ident = player_ident(player);
+            let invalid = || SgfError::Property(ident.into(), value.clone());
+            let points = parse_points(value).ok_or_else(invalid)?;
+            let arrow = match (&points[..], node.first("AA")) {
+                (&[_, _], Some(aa)) => match parse_points(aa).as_deref() {
+                    Some(&[arrow]) => arrow,
+                    _ => return Err(SgfError::Property("AA".into(), aa.into())),
+                },
+                (&[_, _, arrow], None) => arrow,
+                _ => return Err(invalid()),
+            };
+            let mov = Move {
+                from: points[0],
+                to: points[1],
+                arrow,
+            };
+            if player != state.turn() {
+                return Err(SgfError::WrongPlayer(ply, player));
+            }
+            state
+                .try_do_move(mov)
+                .map_err(|err| SgfError::IllegalMove(ply, mov, err))?;
+
+            let mut next = MoveTree::default();
+            for child in &node.children {
+                read_moves(child, state, &mut next, ply + 1)?;
+            }
+            tree.children.push((mov, next));
+            state.undo_move(mov);
+        }
+        None => {
+            for child in &node.children {
+                read_moves(child, state, tree, ply)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SgfError {
+    /// The byte offset of the error and what was expected there.
+    Syntax(usize, &'static str),
+    GameType(String),
+    /// A property with an invalid value.
+    Property(String, String),
+    /// The ply at which the other player moved.
+    WrongPlayer(usize, Player),
+    IllegalMove(usize, Move, IllegalMove),
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(offset, msg) => write!(f, "syntax error at byte {}: {}", offset, msg),
+            Self::GameType(gm) => write!(f, "game type {} is not Amazons (18)", gm),
+            Self::Property(ident, value) => write!(f, "invalid property {}[{}]", ident, value),
+            Self::WrongPlayer(ply, player) => {
+                write!(f, "{:?} moves out of turn at ply {}", player, ply + 1)
+            }
+            Self::IllegalMove(ply, mov, err) => {
+                write!(f, "illegal move {} at ply {}: {}", mov, ply + 1, err)
+            }
+        }
+    }
+}
+
+impl error::Error for SgfError {}
+
+#[test]
+fn sgf_round_trip() {
+    let mut game = Game::new(Box::new(strat::Random), Box::new(strat::Random));
+    game.finish().unwrap();
+    let record = game.record();
+
+    let read = GameRecord::from_sgf(&record.to_sgf()).unwrap();
+    assert_eq!(read.white, record.white);
+    assert_eq!(read.date, record.date);
+    assert_eq!(read.winner, record.winner);
+    assert_eq!(read.moves, record.moves);
+    assert!(read.start == record.start);
+
+    let mut record = record;
+    for (winner, termination) in [
+        (Some(Player::White), Termination::Normal),
+        (Some(Player::Black), Termination::IllegalMove),
+        (Some(Player::White), Termination::TimeForfeit),
+        (Some(Player::Black), Termination::Resignation),
+        (Some(Player::White), Termination::StrategyFailure),
+        (None, Termination::Unterminated),
+    ] {
+        record.winner = winner;
+        record.termination = termination;
+        let read = GameRecord::from_sgf(&record.to_sgf()).unwrap();
+        assert_eq!(read.winner, winner);
+        assert_eq!(read.termination, termination);
+    }
+}
+
+#[test]
+fn sgf_setup_and_variations() {
+    let sgf = "(;GM[18]SZ[4]AW[ab]AB[dd]AA[bb][cc]PL[B]C[setup \\] comment]
+        (;B[dddc]AA[dd];W[abaa]AA[ab])
+        (;B[ddcd]AA[dd]))";
+    let root = parse_collection(sgf).unwrap().swap_remove(0);
+    let (record, tree) = GameRecord::from_sgf_node(&root).unwrap();
+
+    assert_eq!(record.start.to_fen(), "4/Wx2/2x1/3B b 4");
+    assert_eq!(record.moves.len(), 2);
+    assert_eq!(tree.children.len(), 2);
+    assert_eq!(tree.children[1].0, "d3/c3/d3".parse().unwrap());
+    assert_eq!(root.first("C"), Some("setup ] comment"));
+
+    let written = record.to_sgf_tree(&tree);
+    let reread = parse_collection(&written).unwrap().swap_remove(0);
+    let (_, tree_again) = GameRecord::from_sgf_node(&reread).unwrap();
+    assert_eq!(tree, tree_again);
+}
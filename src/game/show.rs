@@ -1,20 +1,38 @@
 use super::*;
-use std::{thread, time};
+use std::{thread, time::Duration};
 
-pub fn display_game(moves: &mut impl Iterator<Item = Move>) {
-    display_end_game(GameState::new(), moves)
+pub fn display_game(moves: &mut impl Iterator<Item = Move>, delay: Duration) {
+    display_end_game(GameState::new(), moves, delay)
 }
 
-pub fn display_end_game(mut config: GameState, moves: &mut impl Iterator<Item = Move>) {
+/// Prints the board after every move, waiting `delay` in between, and who won.
+/// To step through a game interactively, see [`crate::tui::Tui::from_record`].
+pub fn display_end_game(
+    mut config: GameState,
+    moves: &mut impl Iterator<Item = Move>,
+    delay: Duration,
+) {
     let style = BoardStyle::default();
-    println!("{}", config.board.display(&style));
+    println!("{}", config.board().display(&style));
     for mov in moves {
-        thread::sleep(time::Duration::from_secs_f32(0.1));
+        thread::sleep(delay);
         config.do_move(mov);
-        println!("{}", config.board.display(&style));
+        println!(
+            "{:?} plays {} ({})",
+            config.turn().enemy(),
+            mov,
+            mov.community_notation()
+        );
+        println!("{}", config.board().display(&style));
+    }
+    if config.is_finished() {
+        println!(
+            "{} {:?} wins, {:?} can't move",
+            record::result_str(Some(config.turn().enemy())),
+            config.turn().enemy(),
+            config.turn()
+        );
+    } else {
+        println!("{:?} to move, the game isn't over", config.turn());
     }
-    println!(
-        "Oopsie Woopsie, Player {:?} is stucky-wucky >_<",
-        config.turn
-    );
 }
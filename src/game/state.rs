@@ -1,55 +1,155 @@
 use super::*;
+use std::{error, fmt};
 
 #[derive(PartialEq, Eq, Clone)]
 pub struct GameState {
-    pub board: Board,
-    pub turn: Player,
+    board: Board,
+    turn: Player,
+    /// The Zobrist hash of board and turn, kept up to date by `do_move` and `undo_move`.
+    hash: u64,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameState {
+    /// The standard starting position on a 10x10 board.
     pub fn new() -> Self {
-        let black = Slot::Amazon(Player::Black);
-        let white = Slot::Amazon(Player::White);
-
-        let mut board = Board::default();
-        board[(0, 3)] = black;
-        board[(3, 0)] = black;
-        board[(6, 0)] = black;
-        board[(9, 3)] = black;
-
-        board[(0, 6)] = white;
-        board[(3, 9)] = white;
-        board[(6, 9)] = white;
-        board[(9, 6)] = white;
-
-        Self {
-            board,
-            turn: Player::White,
+        Self::with_size(DEFAULT_BOARD_LEN)
+    }
+    /// The standard starting position scaled to a board of side length `len`, which has to be at least 4.
+    /// Each player gets four amazons, a third of the board away from the corners.
+    pub fn with_size(len: usize) -> Self {
+        assert!(
+            len >= 4,
+            "the standard layout needs a board of at least 4x4"
+        );
+        let far = len as u8 - 1;
+        let k = far / 3;
+        let black = [(0, k), (k, 0), (far - k, 0), (far, k)].map(Pos::from);
+        let white = black.map(|p| Pos::from((p.x, far - p.y)));
+        Self::with_amazons(len, &white, &black)
+    }
+    /// A position on an otherwise empty board of side length `len` with white to move.
+    pub fn with_amazons(len: usize, white: &[Pos], black: &[Pos]) -> Self {
+        let mut board = Board::new(len);
+        for (amazons, player) in [(white, Player::White), (black, Player::Black)] {
+            for &p in amazons {
+                assert!(board.contains(p), "amazon {} is off the board", p);
+                assert!(board[p].is_empty(), "two amazons on {}", p);
+                board.set(p, Slot::Amazon(player));
+            }
         }
+        Self::from_board(board, Player::White)
     }
+    #[inline]
     pub fn legal_move(&self, mov: Move) -> bool {
+        self.check_move(mov).is_ok()
+    }
+    /// Checks whether `mov` can be played, and if not, why.
+    pub fn check_move(&self, mov: Move) -> Result<(), IllegalMove> {
+        // a legal move proves that the game is not over yet, so this is only checked on failure
+        self.check_move_unfinished(mov).map_err(|err| {
+            if self.is_finished() {
+                IllegalMove::GameOver
+            } else {
+                err
+            }
+        })
+    }
+    fn check_move_unfinished(&self, mov: Move) -> Result<(), IllegalMove> {
+        use IllegalMove::*;
+
+        if let Some(&p) = [mov.from, mov.to, mov.arrow]
+            .iter()
+            .find(|&&p| !self.board.contains(p))
+        {
+            return Err(OutOfBounds(p));
+        }
         if self.board[mov.from] != Slot::Amazon(self.turn) {
-            return false;
+            return Err(NotYourAmazon(mov.from));
         }
-        if !mov.from.is_reachable(mov.to) || !mov.to.is_reachable(mov.arrow) {
-            return false;
+        if mov.from == mov.to {
+            return Err(AmazonNotMoved);
         }
-        let mut board = self.board.clone();
-        board[mov.from] = Slot::Empty;
-        mov.from.to(mov.to).all(|p| board[p].is_empty())
-            && mov.to.to(mov.arrow).all(|p| board[p].is_empty())
+        if mov.to == mov.arrow {
+            return Err(ArrowOnAmazon);
+        }
+        if !mov.from.is_reachable(mov.to) {
+            return Err(NotOnQueenLine(mov.from, mov.to));
+        }
+        if !mov.to.is_reachable(mov.arrow) {
+            return Err(ArrowNotOnQueenLine(mov.to, mov.arrow));
+        }
+        let mut board = self.board;
+        board.set(mov.from, Slot::Empty);
+        if let Some(p) = mov.from.to(mov.to).find(|&p| !board[p].is_empty()) {
+            return Err(PathBlocked(p));
+        }
+        if let Some(p) = mov.to.to(mov.arrow).find(|&p| !board[p].is_empty()) {
+            return Err(ArrowBlocked(p));
+        }
+        Ok(())
+    }
+    /// Plays `mov` if it is legal, otherwise leaves the position untouched.
+    pub fn try_do_move(&mut self, mov: Move) -> Result<(), IllegalMove> {
+        self.check_move(mov)?;
+        self.do_move(mov);
+        Ok(())
+    }
+    /// Returns an iterator over every legal move of the player to move.
+    /// Each move is produced exactly once and nothing is allocated.
+    pub fn moves(&self) -> MoveIter {
+        MoveIter::new(self)
+    }
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.moves().collect()
+    }
+    pub fn from_board(board: Board, turn: Player) -> Self {
+        Self {
+            hash: zobrist::hash(&board, turn),
+            board,
+            turn,
+        }
+    }
+    /// A hash of the position, equal for equal positions however they were reached.
+    #[inline]
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    #[inline]
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    /// The player to move.
+    #[inline]
+    pub fn turn(&self) -> Player {
+        self.turn
     }
     pub fn do_move(&mut self, mov: Move) {
+        debug_assert_ne!(mov.from, mov.to, "an amazon has to move");
         let source = self.board[mov.from];
-        self.board[mov.from] = Slot::Empty;
-        self.board[mov.to] = source;
-        self.board[mov.arrow] = Slot::Arrow;
+        self.hash ^= zobrist::key(source, mov.from)
+            ^ zobrist::key(source, mov.to)
+            ^ zobrist::key(Slot::Arrow, mov.arrow)
+            ^ zobrist::BLACK_TO_MOVE;
+        self.board.set(mov.from, Slot::Empty);
+        self.board.set(mov.to, source);
+        self.board.set(mov.arrow, Slot::Arrow);
         self.turn = self.turn.enemy();
     }
     pub fn undo_move(&mut self, mov: Move) {
-        self.board[mov.arrow] = Slot::Empty;
-        self.board[mov.from] = self.board[mov.to];
-        self.board[mov.to] = Slot::Empty;
+        let source = self.board[mov.to];
+        self.hash ^= zobrist::key(source, mov.from)
+            ^ zobrist::key(source, mov.to)
+            ^ zobrist::key(Slot::Arrow, mov.arrow)
+            ^ zobrist::BLACK_TO_MOVE;
+        self.board.set(mov.arrow, Slot::Empty);
+        self.board.set(mov.from, self.board[mov.to]);
+        self.board.set(mov.to, Slot::Empty);
         self.turn = self.turn.enemy();
     }
     #[inline]
@@ -63,9 +163,7 @@ impl GameState {
             .collect()
     }
     pub fn is_finished(&self) -> bool {
-        self.find_amazons()
-            .into_iter()
-            .all(|p| self.board.is_trapped(p))
+        (self.board.amazons(self.turn).king_moves() & self.board.empty()).is_empty()
     }
     pub fn winner(&self) -> Option<Player> {
         if self.is_finished() {
@@ -75,3 +173,151 @@ impl GameState {
         }
     }
 }
+
+/// The reason why a move can't be played, see [`GameState::check_move`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    /// The player to move has no legal moves left.
+    GameOver,
+    /// One of the squares of the move is not on the board.
+    OutOfBounds(Pos),
+    /// The square the move starts from doesn't hold an amazon of the player to move.
+    NotYourAmazon(Pos),
+    /// The amazon has to move to a different square.
+    AmazonNotMoved,
+    /// The arrow has to land on a different square than the amazon shooting it.
+    ArrowOnAmazon,
+    /// The amazon's destination is not on a queen line from where it started.
+    NotOnQueenLine(Pos, Pos),
+    /// The arrow's destination is not on a queen line from the amazon.
+    ArrowNotOnQueenLine(Pos, Pos),
+    /// The amazon's path is blocked at the given square.
+    PathBlocked(Pos),
+    /// The arrow's path is blocked at the given square.
+    ArrowBlocked(Pos),
+}
+
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use IllegalMove::*;
+        match self {
+            GameOver => write!(f, "the game is already over"),
+            OutOfBounds(p) => write!(f, "{} is not on the board", p),
+            NotYourAmazon(p) => write!(f, "there is no amazon of the player to move on {}", p),
+            AmazonNotMoved => write!(f, "the amazon has to move"),
+            ArrowOnAmazon => write!(f, "the arrow can't land on the amazon that shoots it"),
+            NotOnQueenLine(a, b) => write!(f, "{} is not on a queen line from {}", b, a),
+            ArrowNotOnQueenLine(a, b) => {
+                write!(
+                    f,
+                    "the arrow target {} is not on a queen line from {}",
+                    b, a
+                )
+            }
+            PathBlocked(p) => write!(f, "the amazon's path is blocked at {}", p),
+            ArrowBlocked(p) => write!(f, "the arrow's path is blocked at {}", p),
+        }
+    }
+}
+
+impl error::Error for IllegalMove {}
+
+#[test]
+fn illegal_move_reasons() {
+    let state = GameState::new();
+    let mov = |from: (u8, u8), to: (u8, u8), arrow: (u8, u8)| Move {
+        from: from.into(),
+        to: to.into(),
+        arrow: arrow.into(),
+    };
+    let check = |m| state.check_move(m);
+
+    assert_eq!(check(mov((0, 6), (0, 5), (0, 4))), Ok(()));
+    assert_eq!(
+        check(mov((0, 3), (0, 4), (0, 5))),
+        Err(IllegalMove::NotYourAmazon((0, 3).into()))
+    );
+    assert_eq!(
+        check(mov((0, 6), (0, 10), (0, 5))),
+        Err(IllegalMove::OutOfBounds((0, 10).into()))
+    );
+    assert_eq!(
+        check(mov((0, 6), (1, 8), (0, 5))),
+        Err(IllegalMove::NotOnQueenLine((0, 6).into(), (1, 8).into()))
+    );
+    assert_eq!(
+        check(mov((0, 6), (0, 2), (0, 5))),
+        Err(IllegalMove::PathBlocked((0, 3).into()))
+    );
+    assert_eq!(
+        check(mov((9, 6), (9, 4), (9, 2))),
+        Err(IllegalMove::ArrowBlocked((9, 3).into()))
+    );
+    assert_eq!(
+        check(mov((0, 6), (0, 5), (0, 6))),
+        Ok(()),
+        "the arrow may be shot back to where the amazon came from"
+    );
+    assert_eq!(
+        check(mov((0, 6), (0, 5), (0, 5))),
+        Err(IllegalMove::ArrowOnAmazon)
+    );
+}
+
+#[test]
+fn incremental_hash() {
+    use rand::prelude::*;
+    let mut rng = rand::thread_rng();
+
+    let mut state = GameState::with_size(8);
+    let start = state.hash();
+    let mut played = Vec::new();
+    while let Some(&mov) = state.legal_moves().choose(&mut rng) {
+        state.do_move(mov);
+        played.push(mov);
+        assert_eq!(state.hash(), zobrist::hash(&state.board, state.turn));
+    }
+    for &mov in played.iter().rev() {
+        state.undo_move(mov);
+    }
+    assert_eq!(state.hash(), start);
+
+    // the same position reached through different move orders
+    let moves =
+        ["a5/a4/a5", "c0/c1/c0", "h5/h4/h5", "f0/f1/f0"].map(|m| m.parse::<Move>().unwrap());
+    let mut a = GameState::with_size(8);
+    let mut b = GameState::with_size(8);
+    for i in [0, 1, 2, 3] {
+        a.do_move(moves[i]);
+    }
+    for i in [2, 3, 0, 1] {
+        b.do_move(moves[i]);
+    }
+    assert!(a == b);
+    assert_ne!(a.hash(), start);
+}
+
+#[test]
+fn few_amazons() {
+    let white = [(0, 4), (4, 4)].map(Pos::from);
+    let black = [(0, 0), (2, 0), (4, 0)].map(Pos::from);
+    let mut state = GameState::with_amazons(5, &white, &black);
+    assert_eq!(state.board.find_amazons(Player::White), white);
+    assert_eq!(state.board.find_amazons(Player::Black), black);
+    assert_eq!(Pos::from((4, 4)).neighbors(5).count(), 3);
+
+    // the generator agrees with checking every combination of squares, for both players
+    let squares = state.board.squares();
+    for _ in 0..2 {
+        let mut legal = 0;
+        for from in squares {
+            for to in squares {
+                for arrow in squares {
+                    legal += state.legal_move(Move { from, to, arrow }) as usize;
+                }
+            }
+        }
+        assert_eq!(state.moves().count(), legal);
+        state.do_move(state.moves().next().unwrap());
+    }
+}
@@ -0,0 +1,430 @@
+use super::*;
+use std::{
+    cmp::Reverse,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
+    thread,
+};
+
+/// The value of a won game, minus the plies it takes to win it, so faster wins are preferred.
+pub const WIN: Value = 1_000_000;
+/// Heuristic values are clamped below this, so they can't be mistaken for a won game.
+const MAX_EVAL: Value = WIN - 1000;
+
+/// The size of the transposition table of [`AlphaBeta`], about 4 MB.
+const TABLE_BITS: u32 = 16;
+
+/// Negamax with alpha-beta pruning over all legal moves, searched in the order of a cheap score,
+/// that remembers positions in a transposition table.
+/// The same position always gets the same move, as the table is cleared for every new position,
+/// but kept while the same position is searched deeper and deeper by [`Iterative`].
+///
+/// With more than one thread, the other threads search the same position at the same time
+/// and share the table (lazy SMP), so the main thread finds more results in it.
+/// The result is only reproducible with one thread.
+#[derive(Clone)]
+pub struct AlphaBeta<H = Reachability> {
+    depth: usize,
+    threads: usize,
+    table: TranspositionTable,
+    /// The hash of the position the table was filled for.
+    root: Option<u64>,
+    heuristic: H,
+}
+
+impl AlphaBeta {
+    pub fn with_depth(depth: usize) -> Self {
+        Self {
+            depth,
+            threads: 1,
+            table: TranspositionTable::new(TABLE_BITS),
+            root: None,
+            heuristic: Reachability,
+        }
+    }
+}
+
+impl<H> AlphaBeta<H> {
+    /// The same search, but evaluating the positions with `heuristic`.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: I) -> AlphaBeta<I> {
+        AlphaBeta {
+            depth: self.depth,
+            threads: self.threads,
+            table: self.table,
+            root: None,
+            heuristic,
+        }
+    }
+}
+
+/// Whether a value returned by a search means the game is decided.
+pub fn is_win(value: Value) -> bool {
+    value.abs() > MAX_EVAL
+}
+
+/// Won values are stored relative to the position, not the root, in the transposition table.
+fn to_table(value: Value, ply: Value) -> Value {
+    match value {
+        v if v > MAX_EVAL => v + ply,
+        v if v < -MAX_EVAL => v - ply,
+        v => v,
+    }
+}
+
+fn from_table(value: Value, ply: Value) -> Value {
+    match value {
+        v if v > MAX_EVAL => v - ply,
+        v if v < -MAX_EVAL => v + ply,
+        v => v,
+    }
+}
+
+/// The state of one search, see [`Search::root`].
+pub struct Search<'a, H = Reachability> {
+    budget: &'a mut Budget,
+    table: &'a TranspositionTable,
+    heuristic: &'a H,
+}
+
+impl<'a> Search<'a> {
+    pub fn new(budget: &'a mut Budget, table: &'a TranspositionTable) -> Self {
+        Self {
+            budget,
+            table,
+            heuristic: &Reachability,
+        }
+    }
+}
+
+impl<'a, H: Heuristic> Search<'a, H> {
+    /// The same search, but evaluating the positions with `heuristic`.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: &'a I) -> Search<'a, I> {
+        Search {
+            budget: self.budget,
+            table: self.table,
+            heuristic,
+        }
+    }
+
+    /// Puts the move found best before in this position first.
+    fn order(&self, state: &GameState, moves: &mut [Move]) {
+        order_moves(state, moves);
+        let best = self.table.probe(state.hash()).and_then(|entry| entry.best);
+        if let Some(i) = best.and_then(|best| moves.iter().position(|&mov| mov == best)) {
+            moves[..=i].rotate_right(1);
+        }
+    }
+
+    /// Searches `depth` plies deep and returns the value of the position for the player to move
+    /// and the best move, `None` if the game is over.
+    /// The result is meaningless if the budget ran out during the search.
+    pub fn root(&mut self, state: &mut GameState, depth: usize) -> (Value, Option<Move>) {
+        self.budget.tick();
+        let mut moves = state.moves().collect::<Vec<_>>();
+        if moves.is_empty() {
+            return (-WIN, None);
+        }
+        self.order(state, &mut moves);
+
+        let depth = depth.max(1);
+        let mut alpha = -WIN - 1;
+        let mut best = moves[0];
+        for mov in moves {
+            state.do_move(mov);
+            let value = -self.negamax(state, -WIN - 1, -alpha, depth - 1, 1);
+            state.undo_move(mov);
+            if value > alpha {
+                alpha = value;
+                best = mov;
+            }
+        }
+        if !self.budget.exhausted() {
+            self.table.store(Entry {
+                hash: state.hash(),
+                depth: depth.min(u8::MAX as usize) as u8,
+                value: alpha,
+                bound: Bound::Exact,
+                best: Some(best),
+            });
+        }
+        (alpha, Some(best))
+    }
+
+    fn negamax(
+        &mut self,
+        state: &mut GameState,
+        mut alpha: Value,
+        beta: Value,
+        depth: usize,
+        ply: Value,
+    ) -> Value {
+        if self.budget.tick() {
+            return 0;
+        }
+        if depth == 0 {
+            if state.is_finished() {
+                return -(WIN - ply);
+            }
+            return self
+                .heuristic
+                .eval(state.turn(), state.board())
+                .clamp(-MAX_EVAL, MAX_EVAL);
+        }
+
+        let depth_key = depth.min(u8::MAX as usize) as u8;
+        if let Some(entry) = self.table.probe(state.hash()) {
+            if entry.depth >= depth_key {
+                let value = from_table(entry.value, ply);
+                match entry.bound {
+                    Bound::Exact => return value,
+                    Bound::Lower if value >= beta => return value,
+                    Bound::Upper if value <= alpha => return value,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = state.moves().collect::<Vec<_>>();
+        if moves.is_empty() {
+            return -(WIN - ply);
+        }
+        self.order(state, &mut moves);
+
+        let original_alpha = alpha;
+        let mut best_value = -WIN - 1;
+        let mut best = moves[0];
+        for mov in moves {
+            state.do_move(mov);
+            let value = -self.negamax(state, -beta, -alpha, depth - 1, ply + 1);
+            state.undo_move(mov);
+            if value > best_value {
+                best_value = value;
+                best = mov;
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if !self.budget.exhausted() {
+            self.table.store(Entry {
+                hash: state.hash(),
+                depth: depth_key,
+                value: to_table(best_value, ply),
+                bound: if best_value <= original_alpha {
+                    Bound::Upper
+                } else if best_value >= beta {
+                    Bound::Lower
+                } else {
+                    Bound::Exact
+                },
+                best: Some(best),
+            });
+        }
+        best_value
+    }
+}
+
+/// Sorts the moves by [`move_score`], best first. Equal moves stay in their order.
+pub fn order_moves(state: &GameState, moves: &mut [Move]) {
+    moves.sort_by_cached_key(|&mov| Reverse(move_score(state, mov)));
+}
+
+/// A cheap guess how good a move is: how many squares the amazon reaches from its destination,
+/// with a bonus for arrows next to enemy amazons.
+#[inline]
+pub fn move_score(state: &GameState, mov: Move) -> u32 {
+    let after = state.board().empty()
+        ^ Bitboard::square(mov.from)
+        ^ Bitboard::square(mov.to)
+        ^ Bitboard::square(mov.arrow);
+    let mobility = Bitboard::square(mov.to).queen_moves(after).count();
+    let near_enemies = state.board().amazons(state.turn().enemy()).king_moves();
+    mobility + 4 * near_enemies.has(mov.arrow) as u32
+}
+
+impl<H: Heuristic + Clone + Send + 'static> Strategy for AlphaBeta<H> {
+    fn name(&self) -> String {
+        let heuristic = self.heuristic.name();
+        match self.threads {
+            1 => format!("AlphaBeta({}, {})", self.depth, heuristic),
+            threads => format!(
+                "AlphaBeta({}, {}, {} threads)",
+                self.depth, heuristic, threads
+            ),
+        }
+    }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        self.search(state, self.depth, &mut Budget::unlimited())
+            .unwrap()
+            .0
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        let mut dup = AlphaBeta::with_depth(self.depth).with_heuristic(self.heuristic.clone());
+        dup.threads = self.threads;
+        Box::new(dup)
+    }
+    fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+}
+
+impl<H: Heuristic + Clone + Send + 'static> DepthSearch for AlphaBeta<H> {
+    fn depth(&self) -> usize {
+        self.depth
+    }
+    fn search(
+        &mut self,
+        state: &GameState,
+        depth: usize,
+        budget: &mut Budget,
+    ) -> Option<(Move, Value)> {
+        if self.root != Some(state.hash()) {
+            self.table.clear();
+            self.root = Some(state.hash());
+        }
+        let table = &self.table;
+        let heuristic = &self.heuristic;
+        let stop = Arc::new(AtomicBool::new(false));
+        let (value, mov) = thread::scope(|scope| {
+            for i in 1..self.threads {
+                let mut budget = budget.helper(&stop);
+                let mut state = state.clone();
+                let heuristic = heuristic.clone();
+                // half of the helpers search a ply deeper, so they fill the table ahead of the others
+                scope.spawn(move || {
+                    Search::new(&mut budget, table)
+                        .with_heuristic(&heuristic)
+                        .root(&mut state, depth + i % 2)
+                });
+            }
+            let result = Search::new(budget, table)
+                .with_heuristic(heuristic)
+                .root(&mut state.clone(), depth);
+            stop.store(true, Relaxed);
+            result
+        });
+        if budget.exhausted() {
+            return None;
+        }
+        mov.map(|mov| (mov, value))
+    }
+    fn is_proven(&self, value: Value) -> bool {
+        is_win(value)
+    }
+}
+
+#[test]
+fn alpha_beta_prefers_faster_wins() {
+    // shooting at a1 traps the black amazon in the corner
+    let state = GameState::from_fen("Bx2/1x2/4/3W w 4").unwrap();
+    for depth in 1..=4 {
+        let (value, mov) = Search::new(&mut Budget::unlimited(), &TranspositionTable::new(12))
+            .root(&mut state.clone(), depth);
+        let mov = mov.unwrap();
+        assert_eq!(value, WIN - 1, "depth {}", depth);
+        assert_eq!(mov.arrow, Pos::from((0, 1)), "depth {}", depth);
+    }
+}
+
+#[test]
+fn alpha_beta_is_deterministic() {
+    let mut state = GameState::with_size(5);
+    let first = AlphaBeta::with_depth(2).find_move(&state);
+    assert_eq!(AlphaBeta::with_depth(2).find_move(&state), first);
+
+    // the full search agrees with plain minimax over all moves
+    fn minimax(state: &mut GameState, depth: usize, ply: Value) -> Value {
+        if depth == 0 && !state.is_finished() {
+            return Reachability
+                .eval(state.turn(), state.board())
+                .clamp(-MAX_EVAL, MAX_EVAL);
+        }
+        let moves = state.moves().collect::<Vec<_>>();
+        if moves.is_empty() {
+            return -(WIN - ply);
+        }
+        let mut best = -WIN - 1;
+        for mov in moves {
+            state.do_move(mov);
+            best = best.max(-minimax(state, depth - 1, ply + 1));
+            state.undo_move(mov);
+        }
+        best
+    }
+    state.do_move(first);
+    let expected = minimax(&mut state.clone(), 2, 0);
+    let (value, _) =
+        Search::new(&mut Budget::unlimited(), &TranspositionTable::new(12)).root(&mut state, 2);
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn alpha_beta_uses_table() {
+    let state = GameState::with_size(5);
+    let table = TranspositionTable::new(12);
+    let mut first = Budget::unlimited();
+    let result = Search::new(&mut first, &table).root(&mut state.clone(), 2);
+    let mut second = Budget::unlimited();
+    assert_eq!(
+        Search::new(&mut second, &table).root(&mut state.clone(), 2),
+        result
+    );
+    // every move at the root is answered from the table
+    assert_eq!(second.nodes(), 1 + state.moves().count() as u64);
+    assert!(first.nodes() > second.nodes());
+
+    let fresh = TranspositionTable::new(12);
+    let mut budget = Budget::unlimited();
+    assert_eq!(
+        Search::new(&mut budget, &fresh)
+            .root(&mut state.clone(), 2)
+            .0,
+        result.0
+    );
+}
+
+#[test]
+fn alpha_beta_threads() {
+    let state = GameState::from_fen("Bx2/1x2/4/3W w 4").unwrap();
+    let mut threaded = super::from_spec("AlphaBeta(3, 4 threads)").unwrap();
+    assert_eq!(threaded.name(), "AlphaBeta(3, Reachability, 4 threads)");
+    assert_eq!(threaded.find_move(&state).arrow, Pos::from((0, 1)));
+
+    let state = GameState::with_size(6);
+    let mut iterative = super::from_spec("Iterative(AlphaBeta(20, 3 threads), 200ms)").unwrap();
+    assert_eq!(
+        iterative.name(),
+        "Iterative(AlphaBeta(20, Reachability, 3 threads), 200ms)"
+    );
+    let start = std::time::Instant::now();
+    assert!(state.check_move(iterative.find_move(&state)).is_ok());
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn alpha_beta_heuristic() {
+    // one ply deep, the value is the best evaluation after any move
+    let mut state = GameState::with_size(5);
+    let expected = state
+        .legal_moves()
+        .into_iter()
+        .map(|mov| {
+            state.do_move(mov);
+            let value = -Mobility.eval(state.turn(), state.board());
+            state.undo_move(mov);
+            value.clamp(-MAX_EVAL, MAX_EVAL)
+        })
+        .max();
+    let (value, _) = Search::new(&mut Budget::unlimited(), &TranspositionTable::new(12))
+        .with_heuristic(&Mobility)
+        .root(&mut state, 1);
+    assert_eq!(Some(value), expected);
+
+    let mut search = AlphaBeta::with_depth(2).with_heuristic(Mobility);
+    assert_eq!(search.name(), "AlphaBeta(2, Mobility)");
+    assert!(state.legal_move(search.find_move(&state)));
+}
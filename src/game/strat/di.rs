@@ -4,18 +4,47 @@ use super::*;
 use std::collections::*;
 
 #[derive(Copy, Clone)]
-pub struct DeepIdiot {
+pub struct DeepIdiot<H = Reachability> {
     width: usize,
     depth: usize,
     memory: usize,
+    heuristic: H,
 }
 
 impl DeepIdiot {
     pub fn with_depth(depth: usize) -> Self {
+        Self::new(depth, 1000, 20)
+    }
+    pub fn new(depth: usize, width: usize, memory: usize) -> Self {
         Self {
+            width,
             depth,
-            memory: 20,
-            width: 1000,
+            memory,
+            heuristic: Reachability,
+        }
+    }
+    /// Like [`DeepIdiot::new`], but rejects arguments the search can't work with:
+    /// every layer keeps `memory` of the `width` positions it tries.
+    pub fn checked(depth: usize, width: usize, memory: usize) -> Result<Self, String> {
+        if depth == 0 || memory == 0 || width < memory {
+            return Err(format!(
+                "DeepIdiot({}, {}, {}) needs a depth and memory of at least 1 \
+                 and a width of at least the memory",
+                depth, width, memory
+            ));
+        }
+        Ok(Self::new(depth, width, memory))
+    }
+}
+
+impl<H> DeepIdiot<H> {
+    /// The same search, but evaluating the positions with `heuristic`.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: I) -> DeepIdiot<I> {
+        DeepIdiot {
+            width: self.width,
+            depth: self.depth,
+            memory: self.memory,
+            heuristic,
         }
     }
 }
@@ -34,7 +63,7 @@ impl<T> PartialEq for Valued<T> {
 impl<T> Eq for Valued<T> {}
 impl<T> std::cmp::PartialOrd for Valued<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.val.cmp(&other.val))
+        Some(self.cmp(other))
     }
 }
 impl<T> std::cmp::Ord for Valued<T> {
@@ -43,44 +72,67 @@ impl<T> std::cmp::Ord for Valued<T> {
     }
 }
 
-impl Strategy for DeepIdiot {
+impl<H: Heuristic + Clone + Send + 'static> Strategy for DeepIdiot<H> {
     fn name(&self) -> String {
-        format!("DeepIdiot({}, {}, {})", self.depth, self.width, self.memory)
+        format!(
+            "DeepIdiot({}, {}, {}, {})",
+            self.depth,
+            self.width,
+            self.memory,
+            self.heuristic.name()
+        )
     }
     fn find_move(&mut self, state: &GameState) -> Move {
-        let me = state.turn;
+        self.search(state, self.depth, &mut Budget::unlimited())
+            .unwrap()
+            .0
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        Box::new(self.clone())
+    }
+}
+
+impl<H: Heuristic> DeepIdiot<H> {
+    /// The moves to the best position found `depth` moves ahead and its value,
+    /// `None` if the budget ran out.
+    fn best_line(
+        &self,
+        state: &GameState,
+        depth: usize,
+        budget: &mut Budget,
+    ) -> Option<(Vec<Move>, Value)> {
+        let me = state.turn();
 
         let mut rng = rand::thread_rng();
         let rng = &mut rng;
 
-        let mut states = vec![vec![(0, state.clone())]];
+        // every position with the index of the position before it and the move in between
+        let mut states = vec![vec![(0, None, state.clone())]];
 
-        let mut initial_moves = vec![];
-
-        for idx in 0..self.depth {
+        for idx in 0..depth {
             let mut best_vals = BinaryHeap::new();
 
             for _ in 0..self.width {
+                if budget.tick() {
+                    return None;
+                }
                 let states = states.last_mut().unwrap();
 
                 let state_idx = rng.gen_range(0..states.len());
-                let state = &mut states[state_idx].1;
-
-                let mov = if state.is_finished() {
-                    let z = (0, 0).into();
-                    Move {
-                        from: z,
-                        to: z,
-                        arrow: z,
+                let state = &mut states[state_idx].2;
+
+                // a finished position is carried over as it is, there is no move to make
+                let mov = (!state.is_finished()).then(|| Random.find_move(state));
+                let mut val = match mov {
+                    Some(mov) => {
+                        state.do_move(mov);
+                        let val = self.heuristic.eval(state.turn(), state.board());
+                        state.undo_move(mov);
+                        val
                     }
-                } else {
-                    Random.find_move(&state)
+                    None => self.heuristic.eval(state.turn().enemy(), state.board()),
                 };
 
-                state.do_move(mov);
-                let mut val = Reachability.eval(state.turn, &state.board);
-                state.undo_move(mov);
-
                 if idx % 2 == 1 {
                     val = -val;
                 }
@@ -98,38 +150,113 @@ impl Strategy for DeepIdiot {
                 .iter()
                 .map(|v| {
                     let (si, mov) = v.data;
-                    let mut state = states.last().unwrap()[si].1.clone();
-                    state.do_move(mov);
-                    (si, state)
+                    let mut state = states.last().unwrap()[si].2.clone();
+                    if let Some(mov) = mov {
+                        state.do_move(mov);
+                    }
+                    (si, mov, state)
                 })
                 .collect::<Vec<_>>();
             states.push(new_states);
-
-            if idx == 0 {
-                initial_moves = best_vals.iter().map(|v| v.data.1).collect();
-            }
         }
 
         // finding the best outcome
         let mut best_val = MIN_VAL;
         let mut best_last = 0;
-        for i in 0..self.memory {
-            let val = Reachability.eval(me, &states.last().unwrap()[i].1.board);
+        for (i, (_, _, leaf)) in states.last().unwrap().iter().enumerate() {
+            let val = self.heuristic.eval(me, leaf.board());
             if val > best_val {
                 best_val = val;
                 best_last = i;
             }
         }
 
-        // backtracking
-        let mut mov_idx = best_last;
-        for i in (2..self.depth).rev() {
-            mov_idx = states[i][mov_idx].0;
+        // backtracking through every layer down to the moves from the root
+        let mut line = Vec::with_capacity(depth);
+        let mut idx = best_last;
+        for layer in states[1..].iter().rev() {
+            let (parent, mov, _) = layer[idx];
+            line.extend(mov);
+            idx = parent;
         }
+        line.reverse();
 
-        initial_moves[mov_idx]
+        Some((line, best_val))
     }
-    fn dup(&self) -> Box<dyn Strategy> {
-        Box::new(self.clone())
+}
+
+impl<H: Heuristic + Clone + Send + 'static> DepthSearch for DeepIdiot<H> {
+    fn depth(&self) -> usize {
+        self.depth
+    }
+    fn search(
+        &mut self,
+        state: &GameState,
+        depth: usize,
+        budget: &mut Budget,
+    ) -> Option<(Move, Value)> {
+        self.best_line(state, depth, budget)
+            .and_then(|(line, value)| Some((*line.first()?, value)))
+    }
+}
+
+#[test]
+fn deep_idiot_arguments() {
+    // fewer positions tried than kept
+    let mut narrow = DeepIdiot::new(2, 10, 20);
+    let state = GameState::with_size(6);
+    assert!(state.legal_move(narrow.find_move(&state)));
+
+    assert!(from_spec("DeepIdiot(2, 20, 20)").is_ok());
+    for spec in [
+        "DeepIdiot(2, 10, 20)",
+        "DeepIdiot(2, 0, 0)",
+        "DeepIdiot(0, 10, 5)",
+    ] {
+        assert!(from_spec(spec).is_err(), "{}", spec);
     }
 }
+
+#[test]
+fn deep_idiot_line() {
+    let state = GameState::with_size(6);
+    let mut di = DeepIdiot::new(3, 200, 10);
+    for _ in 0..10 {
+        // the value is the one of the position the line leads to
+        let (line, value) = di.best_line(&state, 3, &mut Budget::unlimited()).unwrap();
+        assert_eq!(line.len(), 3);
+        let mut end = state.clone();
+        for &mov in &line {
+            assert!(end.legal_move(mov));
+            end.do_move(mov);
+        }
+        assert_eq!(Reachability.eval(state.turn(), end.board()), value);
+    }
+    let (mov, _) = di.search(&state, 3, &mut Budget::unlimited()).unwrap();
+    assert!(state.legal_move(mov));
+}
+
+#[test]
+fn deep_idiot_finished() {
+    // white can trap black with its first move, the layers after that have no moves
+    let state = GameState::from_fen("Bx2/x3/4/3W w 4").unwrap();
+    let di = DeepIdiot::new(3, 100, 10);
+    for _ in 0..10 {
+        let (line, value) = di.best_line(&state, 3, &mut Budget::unlimited()).unwrap();
+        let mut end = state.clone();
+        for &mov in &line {
+            assert!(end.legal_move(mov));
+            end.do_move(mov);
+        }
+        assert_eq!(Reachability.eval(state.turn(), end.board()), value);
+        assert_eq!(
+            end.hash(),
+            GameState::from_fen(&end.to_fen()).unwrap().hash()
+        );
+    }
+
+    let trapped = GameState::from_fen("Bx2/xx2/4/3W b 4").unwrap();
+    let (line, value) = di.best_line(&trapped, 2, &mut Budget::unlimited()).unwrap();
+    assert!(line.is_empty());
+    assert_eq!(value, MIN_VAL);
+}
@@ -0,0 +1,267 @@
+use super::*;
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Extra time an engine gets on top of its time limit, for process and pipe overhead.
+const GRACE: Duration = Duration::from_millis(500);
+
+/// A strategy played by another program that speaks the protocol of [`crate::protocol`],
+/// like the `engine` binary of this crate.
+///
+/// The process is started on the first move and restarted after it crashed or timed out.
+/// Every move it is sent the whole position, so one process could even play both sides.
+pub struct External {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    time_limit: Option<Duration>,
+    process: Option<Process>,
+}
+
+impl External {
+    pub fn new(
+        program: impl Into<String>,
+        args: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            timeout: DEFAULT_TIMEOUT,
+            time_limit: None,
+            process: None,
+        }
+    }
+
+    /// Sets how long to wait for any answer of the engine when there is no time limit.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn process(&mut self) -> Result<&mut Process, StrategyError> {
+        if self.process.is_none() {
+            self.process = Some(Process::spawn(&self.program, &self.args)?);
+        }
+        Ok(self.process.as_mut().unwrap())
+    }
+
+    fn play(&mut self, state: &GameState) -> Result<Action, StrategyError> {
+        let timeout = self.time_limit.map_or(self.timeout, |limit| limit + GRACE);
+        let time_limit = self.time_limit;
+        let process = self.process()?;
+
+        if process.time_limit != time_limit {
+            let settings = match time_limit {
+                // byo-yomi without stones means no time limit
+                None => "time_settings 0 1 0".to_string(),
+                Some(limit) => format!("time_settings 0 {} 1", limit.as_secs_f64()),
+            };
+            process.command(&settings, timeout)?;
+            process.time_limit = time_limit;
+        }
+        process.command(&format!("position {}", state.to_fen()), timeout)?;
+        let answer = process.command("genmove", timeout)?;
+        if answer.eq_ignore_ascii_case("resign") {
+            return Ok(Action::Resign);
+        }
+        answer
+            .parse()
+            .map(Action::Move)
+            .map_err(|err| StrategyError::Protocol(format!("'{}' is not a move: {}", answer, err)))
+    }
+}
+
+impl Strategy for External {
+    fn name(&self) -> String {
+        let mut command = vec![self.program.as_str()];
+        command.extend(self.args.iter().map(String::as_str));
+        format!("External({})", command.join(" "))
+    }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        match self.act(state) {
+            Ok(Action::Move(mov)) => mov,
+            Ok(action) => panic!("{}: can't {:?} here", self.name(), action),
+            Err(err) => panic!("{}: {}", self.name(), err),
+        }
+    }
+    fn act(&mut self, state: &GameState) -> Result<Action, StrategyError> {
+        let result = self.play(state);
+        if result.is_err() {
+            // whatever state the engine is in, it can't be trusted anymore
+            self.process = None;
+        }
+        result
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        Box::new(Self {
+            program: self.program.clone(),
+            args: self.args.clone(),
+            timeout: self.timeout,
+            time_limit: self.time_limit,
+            process: None,
+        })
+    }
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+}
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    responses: mpsc::Receiver<String>,
+    /// The time limit the engine has been told about.
+    time_limit: Option<Duration>,
+}
+
+impl Process {
+    fn spawn(program: &str, args: &[String]) -> Result<Self, StrategyError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                StrategyError::Crashed(format!("could not start {}: {}", program, err))
+            })?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        // reads the responses, which end with an empty line, in the background so waiting can time out
+        let (sender, responses) = mpsc::channel();
+        thread::spawn(move || {
+            let mut response = String::new();
+            for line in stdout.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.trim().is_empty() {
+                    if !response.is_empty() && sender.send(response.split_off(0)).is_err() {
+                        break;
+                    }
+                } else {
+                    response += &line;
+                    response += "\n";
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            responses,
+            // the engine starts out without a time limit
+            time_limit: None,
+        })
+    }
+
+    /// Sends a command and waits for its result.
+    fn command(&mut self, command: &str, timeout: Duration) -> Result<String, StrategyError> {
+        if let Err(err) = writeln!(self.stdin, "{}", command).and_then(|_| self.stdin.flush()) {
+            return Err(self.crashed(err.to_string()));
+        }
+        let response = match self.responses.recv_timeout(timeout) {
+            Ok(response) => response,
+            Err(RecvTimeoutError::Timeout) => return Err(StrategyError::Timeout(timeout)),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(self.crashed("no more output".into()))
+            }
+        };
+        let text = response
+            .get(1..)
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim()
+            .to_string();
+        match response.chars().next() {
+            Some('=') => Ok(text),
+            Some('?') => Err(StrategyError::Protocol(format!(
+                "'{}' failed: {}",
+                command, text
+            ))),
+            _ => Err(StrategyError::Protocol(format!(
+                "unexpected answer '{}' to '{}'",
+                response.trim(),
+                command
+            ))),
+        }
+    }
+
+    fn crashed(&mut self, reason: String) -> StrategyError {
+        // give the process a moment to actually exit, to report how it ended
+        let deadline = Instant::now() + Duration::from_millis(100);
+        while Instant::now() < deadline {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return StrategyError::Crashed(format!("{}, {}", reason, status));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        StrategyError::Crashed(reason)
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit").and_then(|_| self.stdin.flush());
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+fn shell(script: &str) -> External {
+    External::new("sh", ["-c", script]).with_timeout(Duration::from_millis(500))
+}
+
+#[test]
+fn external_engine() {
+    let state = GameState::new();
+    let mut engine = shell("while read line; do echo '= a6/a5/a6'; echo; done");
+    assert_eq!(
+        engine.act(&state),
+        Ok(Action::Move("a6/a5/a6".parse().unwrap()))
+    );
+
+    let mut resigning = shell("while read line; do echo '= resign'; echo; done");
+    assert_eq!(resigning.act(&state), Ok(Action::Resign));
+
+    let mut crashing = shell("read line; exit 3");
+    assert!(matches!(
+        crashing.act(&state),
+        Err(StrategyError::Crashed(_))
+    ));
+
+    let mut hanging = shell("sleep 5");
+    assert_eq!(
+        hanging.act(&state),
+        Err(StrategyError::Timeout(Duration::from_millis(500)))
+    );
+
+    let mut confused = shell("while read line; do echo '? no'; echo; done");
+    assert!(matches!(
+        confused.act(&state),
+        Err(StrategyError::Protocol(_))
+    ));
+
+    assert_eq!(
+        from_spec("External(sh -c true)").unwrap().name(),
+        "External(sh -c true)"
+    );
+    // the spec is checked for the prefix without slicing through a character
+    assert!(from_spec("Minimax(é)xyzw").is_err());
+    assert!(from_spec("Extérnal(sh)").is_err());
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+/// The penalty for an amazon with no, one or two empty neighbors. One arrow can enclose an amazon
+/// with few neighbors, and an amazon without any can never move again.
+const DANGER: [Value; 3] = [100, 40, 12];
+/// How much more an empty neighbor counts than any other square the amazon reaches.
+const NEIGHBOR_WEIGHT: Value = 2;
+/// How much less a square reachable in two moves counts than one reachable in one.
+const SECOND_DIVISOR: Value = 4;
+
+/// How free a single amazon is to move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AmazonMobility {
+    /// The squares the amazon reaches with one queen move.
+    pub reach: u32,
+    /// The squares it reaches with two queen moves, the ways it can still escape if its
+    /// direct surroundings are shot at.
+    pub second: u32,
+    /// The empty squares next to it.
+    pub neighbors: u32,
+}
+
+impl AmazonMobility {
+    pub fn new(board: &Board, p: Pos) -> Self {
+        let empty = board.empty();
+        let amazon = Bitboard::square(p);
+        let reach = amazon.queen_moves(empty);
+        Self {
+            reach: reach.count(),
+            second: ((reach.queen_moves(empty | amazon) | reach) & !amazon).count(),
+            neighbors: (amazon.king_moves() & empty).count(),
+        }
+    }
+
+    /// The value of the amazon, with a large penalty if it is about to be enclosed.
+    pub fn score(self) -> Value {
+        let danger = DANGER.get(self.neighbors as usize).copied().unwrap_or(0);
+        self.reach as Value
+            + self.second as Value / SECOND_DIVISOR
+            + NEIGHBOR_WEIGHT * self.neighbors as Value
+            - danger
+    }
+}
+
+/// The mobility of every amazon on its own, so one nearly trapped amazon
+/// isn't made up for by the freedom of the others.
+#[derive(Copy, Clone)]
+pub struct Mobility;
+
+impl Heuristic for Mobility {
+    fn name(&self) -> String {
+        "Mobility".into()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        let score = |player| -> Value {
+            board
+                .amazons(player)
+                .iter()
+                .map(|p| AmazonMobility::new(board, p).score())
+                .sum()
+        };
+        decided(player, board).unwrap_or_else(|| score(player) - score(player.enemy()))
+    }
+}
+
+#[test]
+fn mobility() {
+    let board = *GameState::from_fen("W3/xx2/4/3B w 4").unwrap().board();
+    let white = AmazonMobility::new(&board, Pos::from((0, 0)));
+    assert_eq!(
+        white,
+        AmazonMobility {
+            reach: 3,
+            second: 3 + 7,
+            neighbors: 1,
+        }
+    );
+    assert_eq!(white.score(), 3 + 10 / 4 + 2 - 40);
+
+    // the same arrows, but one amazon is nearly enclosed
+    let balanced = *GameState::from_fen("6/1W4/xx4/6/6/W4B w 6")
+        .unwrap()
+        .board();
+    let cornered = *GameState::from_fen("W5/xx4/6/6/6/W4B w 6").unwrap().board();
+    assert!(Mobility.eval(Player::White, &balanced) > Mobility.eval(Player::White, &cornered));
+
+    assert_eq!(Mobility.eval(Player::White, GameState::new().board()), 0);
+    let trapped = *GameState::from_fen("Bx2/xx2/4/3W w 4").unwrap().board();
+    assert_eq!(Mobility.eval(Player::Black, &trapped), MIN_VAL);
+}
@@ -0,0 +1,116 @@
+use super::*;
+use std::sync::Arc;
+
+pub mod mobility;
+pub mod territory;
+pub mod weighted;
+
+pub use {mobility::*, territory::*, weighted::*};
+
+pub type Value = i32;
+pub const MIN_VAL: Value = -i32::MAX;
+pub const MAX_VAL: Value = i32::MAX;
+
+pub trait Heuristic {
+    fn name(&self) -> String;
+    fn eval(&self, player: Player, board: &Board) -> Value;
+}
+
+/// A heuristic chosen at runtime, e.g. by [`heuristic::from_spec`](from_spec),
+/// that can be shared by strategies on different threads.
+pub type SharedHeuristic = Arc<dyn Heuristic + Send + Sync>;
+
+impl<H: Heuristic + ?Sized> Heuristic for Arc<H> {
+    fn name(&self) -> String {
+        (**self).name()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        (**self).eval(player, board)
+    }
+}
+
+/// Creates a heuristic from its name or a spec like `Weighted(Mobility: 2..0, Territory: 0..1)`,
+/// ignoring case and whitespace.
+pub fn from_spec(spec: &str) -> Result<SharedHeuristic, String> {
+    let spec = spec
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    if spec.starts_with("weighted(") {
+        return Ok(Arc::new(spec.parse::<Weighted>()?));
+    }
+    Ok(match spec.as_str() {
+        "reachability" => Arc::new(Reachability),
+        "territory" => Arc::new(Territory),
+        "queenterritory" => Arc::new(QueenTerritory),
+        "kingterritory" => Arc::new(KingTerritory),
+        "mobility" => Arc::new(Mobility),
+        _ => {
+            return Err(format!(
+                "unknown heuristic '{}', known are: Reachability, Territory, QueenTerritory, \
+                 KingTerritory, Mobility, Weighted(heuristic: weight[..late weight], ...)",
+                spec
+            ))
+        }
+    })
+}
+
+#[derive(Copy, Clone)]
+pub struct Reachability;
+
+impl Heuristic for Reachability {
+    fn name(&self) -> String {
+        "Reachability".into()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        fn eval_player(player: Player, board: &Board) -> Value {
+            board
+                .find_amazons(player)
+                .into_iter()
+                .map(|a| board.reach_count(a) as Value)
+                .sum()
+        }
+        let me_val = eval_player(player, board);
+        let enemy_val = eval_player(player.enemy(), board);
+
+        if me_val == 0 {
+            MIN_VAL
+        } else if enemy_val == 0 {
+            MAX_VAL
+        } else {
+            me_val - enemy_val
+        }
+    }
+}
+
+/// Tells whether one of the players can't move anymore, which decides the game.
+fn decided(player: Player, board: &Board) -> Option<Value> {
+    let empty = board.empty();
+    if board.amazons(player).queen_moves(empty).is_empty() {
+        Some(MIN_VAL)
+    } else if board.amazons(player.enemy()).queen_moves(empty).is_empty() {
+        Some(MAX_VAL)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn heuristic_specs() {
+    for spec in [
+        "Minimax(2, Territory)",
+        "LookAhead(QueenTerritory)",
+        "DeepIdiot(2, 100, 5, Mobility)",
+        "Iterative(Minimax(3, KingTerritory), 2s)",
+        "AlphaBeta(2, Territory)",
+        "Iterative(AlphaBeta(3, Mobility, 2 threads), 2s)",
+    ] {
+        assert_eq!(strat::from_spec(spec).unwrap().name(), spec);
+    }
+    assert_eq!(
+        strat::from_spec("minimax(2)").unwrap().name(),
+        "Minimax(2, Reachability)"
+    );
+    assert!(strat::from_spec("Minimax(2, Nonsense)").is_err());
+}
@@ -0,0 +1,224 @@
+use super::*;
+use std::cmp::Ordering;
+
+const SQUARES: usize = MAX_BOARD_LEN * MAX_BOARD_LEN;
+/// The distance of squares a player can't reach at all.
+pub const UNREACHABLE: u8 = u8::MAX;
+
+/// How much a square both players reach equally fast counts for the player to move.
+const TEMPO: f64 = 0.2;
+/// Heuristic values are tenths of a square of territory.
+const SCALE: f64 = 10.0;
+/// The weights of t1, t2, c1 and c2 in [`Territory`] when every empty square is contested.
+const OPEN: [f64; 4] = [0.3, 0.3, 0.2, 0.2];
+/// The weights when the players are walled off from each other.
+const CLOSED: [f64; 4] = [0.8, 0.2, 0.0, 0.0];
+
+/// The fewest queen moves and king moves each player needs to reach every square,
+/// found by a breadth first search from all amazons of the player at once.
+pub struct Distances {
+    queen: [[u8; SQUARES]; 2],
+    king: [[u8; SQUARES]; 2],
+}
+
+impl Distances {
+    pub fn new(board: &Board) -> Self {
+        let mut distances = Self {
+            queen: [[UNREACHABLE; SQUARES]; 2],
+            king: [[UNREACHABLE; SQUARES]; 2],
+        };
+        let empty = board.empty();
+        for player in [Player::White, Player::Black] {
+            let amazons = board.amazons(player);
+            search(&mut distances.queen[player as usize], amazons, |front| {
+                front.queen_moves(empty)
+            });
+            search(&mut distances.king[player as usize], amazons, |front| {
+                front.king_moves() & empty
+            });
+        }
+        distances
+    }
+
+    /// The queen moves `player` needs to reach `p`, [`UNREACHABLE`] if it can't.
+    #[inline]
+    pub fn queen(&self, player: Player, p: Pos) -> u8 {
+        self.queen[player as usize][index(p)]
+    }
+
+    /// The king moves `player` needs to reach `p`, [`UNREACHABLE`] if it can't.
+    #[inline]
+    pub fn king(&self, player: Player, p: Pos) -> u8 {
+        self.king[player as usize][index(p)]
+    }
+}
+
+#[inline]
+fn index(p: Pos) -> usize {
+    p.y as usize * MAX_BOARD_LEN + p.x as usize
+}
+
+/// Writes the number of `step`s from `start` to every square into `distances`.
+fn search(distances: &mut [u8; SQUARES], start: Bitboard, step: impl Fn(Bitboard) -> Bitboard) {
+    let mut reached = start;
+    let mut front = start;
+    let mut distance = 0;
+    while !front.is_empty() {
+        distance += 1;
+        front = step(front) & !reached;
+        reached |= front;
+        for p in front {
+            distances[index(p)] = distance;
+        }
+    }
+}
+
+/// The terms of the territory evaluation after Lieberum, each from the view of one player:
+/// - `t1` and `t2` count the empty squares the player reaches with fewer queen or king moves,
+///   minus those the enemy reaches faster.
+/// - `c1` and `c2` weigh how much faster, which matters early when few squares are settled.
+/// - `w` says how contested the board is, from 0 when the players are walled off from each other
+///   to 1 when every empty square is reached by both equally fast.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerritoryTerms {
+    pub t1: f64,
+    pub t2: f64,
+    pub c1: f64,
+    pub c2: f64,
+    pub w: f64,
+}
+
+impl TerritoryTerms {
+    /// The terms for `player`, who is assumed to move next and so gets squares both reach equally fast.
+    pub fn new(player: Player, board: &Board) -> Self {
+        let distances = Distances::new(board);
+        let enemy = player.enemy();
+
+        // who gets a square, given the distances of both players
+        let owner = |mine: u8, theirs: u8| match mine.cmp(&theirs) {
+            _ if mine == UNREACHABLE && theirs == UNREACHABLE => 0.0,
+            Ordering::Less => 1.0,
+            Ordering::Equal => TEMPO,
+            Ordering::Greater => -1.0,
+        };
+        let power = |distance: u8| match distance {
+            UNREACHABLE => 0.0,
+            d => 0.5f64.powi(d as i32),
+        };
+
+        let mut terms = Self {
+            t1: 0.0,
+            t2: 0.0,
+            c1: 0.0,
+            c2: 0.0,
+            w: 0.0,
+        };
+        let empty = board.empty();
+        for p in empty {
+            let (q1, q2) = (distances.queen(player, p), distances.queen(enemy, p));
+            let (k1, k2) = (distances.king(player, p), distances.king(enemy, p));
+            terms.t1 += owner(q1, q2);
+            terms.t2 += owner(k1, k2);
+            terms.c1 += 2.0 * (power(q1) - power(q2));
+            terms.c2 += ((k2 as f64 - k1 as f64) / 6.0).clamp(-1.0, 1.0);
+            if q1 != UNREACHABLE && q2 != UNREACHABLE {
+                terms.w += 0.5f64.powi(q1.abs_diff(q2) as i32);
+            }
+        }
+        terms.w /= empty.count().max(1) as f64;
+        terms
+    }
+}
+
+/// The squares a player reaches with fewer queen moves than the enemy (t1).
+#[derive(Copy, Clone)]
+pub struct QueenTerritory;
+
+impl Heuristic for QueenTerritory {
+    fn name(&self) -> String {
+        "QueenTerritory".into()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        decided(player, board)
+            .unwrap_or_else(|| (SCALE * TerritoryTerms::new(player, board).t1).round() as Value)
+    }
+}
+
+/// The squares a player reaches with fewer king moves than the enemy (t2).
+#[derive(Copy, Clone)]
+pub struct KingTerritory;
+
+impl Heuristic for KingTerritory {
+    fn name(&self) -> String {
+        "KingTerritory".into()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        decided(player, board)
+            .unwrap_or_else(|| (SCALE * TerritoryTerms::new(player, board).t2).round() as Value)
+    }
+}
+
+/// All [`TerritoryTerms`] together, weighted by how contested the board is:
+/// the distance terms count early on and the territory by queen moves counts in the end.
+#[derive(Copy, Clone)]
+pub struct Territory;
+
+impl Heuristic for Territory {
+    fn name(&self) -> String {
+        "Territory".into()
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        decided(player, board).unwrap_or_else(|| {
+            let terms = TerritoryTerms::new(player, board);
+            let weight = |i: usize| terms.w * OPEN[i] + (1.0 - terms.w) * CLOSED[i];
+            let value = weight(0) * terms.t1
+                + weight(1) * terms.t2
+                + weight(2) * terms.c1
+                + weight(3) * terms.c2;
+            (SCALE * value).round() as Value
+        })
+    }
+}
+
+#[test]
+fn territory() {
+    // the arrows wall white off with seven squares on the left and black with three on the right
+    let board = *GameState::from_fen("W1x1/2x1/2x1/2xB w 4").unwrap().board();
+    let distances = Distances::new(&board);
+    assert_eq!(distances.queen(Player::White, Pos::from((1, 2))), 2);
+    assert_eq!(distances.king(Player::White, Pos::from((1, 2))), 2);
+    assert_eq!(distances.queen(Player::White, Pos::from((0, 3))), 1);
+    assert_eq!(distances.king(Player::White, Pos::from((0, 3))), 3);
+    assert_eq!(
+        distances.queen(Player::Black, Pos::from((0, 0))),
+        UNREACHABLE
+    );
+
+    let terms = TerritoryTerms::new(Player::White, &board);
+    assert_eq!(terms.w, 0.0);
+    assert_eq!(terms.t1, 4.0);
+    assert_eq!(terms.t2, 4.0);
+    assert_eq!(TerritoryTerms::new(Player::Black, &board).t1, -4.0);
+    assert_eq!(QueenTerritory.eval(Player::White, &board), 40);
+    assert_eq!(Territory.eval(Player::White, &board), 40);
+
+    // the same for both players in the symmetric start position
+    let board = *GameState::with_size(6).board();
+    assert!(TerritoryTerms::new(Player::White, &board).w > 0.0);
+    for heuristic in [
+        &Territory as &dyn Heuristic,
+        &QueenTerritory,
+        &KingTerritory,
+    ] {
+        assert_eq!(
+            heuristic.eval(Player::White, &board),
+            heuristic.eval(Player::Black, &board),
+            "{}",
+            heuristic.name()
+        );
+    }
+
+    let board = *GameState::from_fen("Bx2/xx2/4/3W w 4").unwrap().board();
+    assert_eq!(Territory.eval(Player::Black, &board), MIN_VAL);
+    assert_eq!(Territory.eval(Player::White, &board), MAX_VAL);
+}
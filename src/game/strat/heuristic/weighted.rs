@@ -0,0 +1,225 @@
+use super::*;
+use std::{str::FromStr, sync::Arc};
+
+/// How far the game is, from 0 at the start to 1 when every square is filled:
+/// the share of the squares that were empty at the start that have an arrow on them.
+pub fn phase(board: &Board) -> f64 {
+    let arrows = board.arrows().count();
+    let empty = board.empty().count();
+    match arrows + empty {
+        0 => 1.0,
+        start => arrows as f64 / start as f64,
+    }
+}
+
+#[derive(Clone)]
+struct Term {
+    heuristic: SharedHeuristic,
+    early: f64,
+    late: f64,
+}
+
+/// The weighted sum of several heuristics, with weights that go linearly from their value at the
+/// start of the game to their value at the end, see [`phase`].
+///
+/// In specs it's written like `Weighted(Mobility: 2..0, Territory: 0..1, Reachability: 1)`,
+/// with one weight for the whole game or the weight at the start and at the end.
+/// Weights can't be negative, as a heuristic that says a game is decided is trusted regardless.
+#[derive(Clone, Default)]
+pub struct Weighted {
+    terms: Vec<Term>,
+}
+
+impl Weighted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `heuristic` with the weight `early` at the start of the game and `late` at the end.
+    pub fn with(
+        self,
+        heuristic: impl Heuristic + Send + Sync + 'static,
+        early: f64,
+        late: f64,
+    ) -> Self {
+        self.with_shared(Arc::new(heuristic), early, late)
+    }
+
+    pub fn with_shared(mut self, heuristic: SharedHeuristic, early: f64, late: f64) -> Self {
+        assert!(early >= 0.0 && late >= 0.0, "negative weight");
+        self.terms.push(Term {
+            heuristic,
+            early,
+            late,
+        });
+        self
+    }
+
+    /// The weights at the start and at the end of the game of every heuristic, one after another.
+    pub fn weights(&self) -> Vec<f64> {
+        self.terms
+            .iter()
+            .flat_map(|term| [term.early, term.late])
+            .collect()
+    }
+
+    /// The same heuristics with other weights, in the order of [`Weighted::weights`].
+    pub fn with_weights(&self, weights: &[f64]) -> Self {
+        assert_eq!(
+            weights.len(),
+            2 * self.terms.len(),
+            "wrong number of weights"
+        );
+        assert!(weights.iter().all(|&w| w >= 0.0), "negative weight");
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .zip(weights.chunks(2))
+                .map(|(term, weights)| Term {
+                    heuristic: term.heuristic.clone(),
+                    early: weights[0],
+                    late: weights[1],
+                })
+                .collect(),
+        }
+    }
+
+    /// Parses the terms of `Weighted(...)`, like `Mobility: 2..0, Territory: 1`.
+    fn parse_terms(spec: &str) -> Result<Self, String> {
+        let number = |n: &str| {
+            n.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|&n| n >= 0.0)
+                .ok_or_else(|| format!("'{}' is not a non-negative weight", n))
+        };
+        // a term can be a weighted heuristic itself, with commas and colons inside parentheses
+        split_args(spec)
+            .into_iter()
+            .try_fold(Self::new(), |weighted, term| {
+                let (name, weights) = term
+                    .rsplit_once(':')
+                    .ok_or_else(|| format!("missing weight in '{}'", term))?;
+                let (early, late) = match weights.split_once("..") {
+                    Some((early, late)) => (number(early)?, number(late)?),
+                    None => (number(weights)?, number(weights)?),
+                };
+                Ok(weighted.with_shared(from_spec(name)?, early, late))
+            })
+    }
+}
+
+impl FromStr for Weighted {
+    type Err = String;
+
+    /// Parses a spec like `Weighted(Mobility: 2..0, Territory: 1)`, ignoring case and whitespace.
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let normalized = spec
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        match normalized
+            .strip_prefix("weighted(")
+            .and_then(|terms| terms.strip_suffix(')'))
+        {
+            Some(terms) => Self::parse_terms(terms),
+            None => Err(format!(
+                "'{}' is not like Weighted(heuristic: weight, ...)",
+                spec
+            )),
+        }
+    }
+}
+
+impl Heuristic for Weighted {
+    fn name(&self) -> String {
+        let terms = self
+            .terms
+            .iter()
+            .map(|term| {
+                if term.early == term.late {
+                    format!("{}: {}", term.heuristic.name(), term.early)
+                } else {
+                    format!("{}: {}..{}", term.heuristic.name(), term.early, term.late)
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("Weighted({})", terms.join(", "))
+    }
+    fn eval(&self, player: Player, board: &Board) -> Value {
+        if let Some(value) = decided(player, board) {
+            return value;
+        }
+        let phase = phase(board);
+        let mut sum = 0.0;
+        for term in &self.terms {
+            let weight = term.early + (term.late - term.early) * phase;
+            if weight == 0.0 {
+                continue;
+            }
+            match term.heuristic.eval(player, board) {
+                // a decided game stays decided, no matter the weight
+                value @ (MIN_VAL | MAX_VAL) => return value,
+                value => sum += weight * value as f64,
+            }
+        }
+        // only a decided game gets the extreme values
+        sum.round()
+            .clamp(MIN_VAL as f64 + 1.0, MAX_VAL as f64 - 1.0) as Value
+    }
+}
+
+#[test]
+fn weighted() {
+    let start = *GameState::with_size(6).board();
+    assert_eq!(phase(&start), 0.0);
+    let mut board = start;
+    for x in 0..6 {
+        board.set((x, 2), Slot::Arrow);
+        board.set((x, 3), Slot::Arrow);
+    }
+    assert_eq!(phase(&board), 12.0 / 28.0);
+
+    let blend = Weighted::new()
+        .with(Mobility, 2.0, 0.0)
+        .with(Territory, 0.0, 1.0);
+    assert_eq!(blend.name(), "Weighted(Mobility: 2..0, Territory: 0..1)");
+    let player = Player::White;
+    assert_eq!(
+        blend.eval(player, &start),
+        2 * Mobility.eval(player, &start)
+    );
+    let late = (2.0 * (1.0 - phase(&board))) * Mobility.eval(player, &board) as f64
+        + phase(&board) * Territory.eval(player, &board) as f64;
+    assert_eq!(blend.eval(player, &board), late.round() as Value);
+
+    let spec = "Weighted(Mobility: 2..0, Territory: 0.5..1, Reachability: 1)";
+    assert_eq!(from_spec(spec).unwrap().name(), spec);
+    assert_eq!(
+        strat::from_spec(&format!("Minimax(2, {})", spec))
+            .unwrap()
+            .name(),
+        format!("Minimax(2, {})", spec)
+    );
+    let doubled = blend.with_weights(&[4.0, 0.0, 0.0, 2.0]);
+    assert_eq!(doubled.weights(), [4.0, 0.0, 0.0, 2.0]);
+    assert_eq!(
+        doubled.name().parse::<Weighted>().unwrap().name(),
+        doubled.name()
+    );
+    assert!(from_spec("Weighted(Mobility)").is_err());
+    assert!(from_spec("Weighted(Mobility: x)").is_err());
+    assert!(from_spec("Weighted(Mobility: -1..0)").is_err());
+
+    let nested = "Weighted(Weighted(Mobility: 1, Territory: 0..1): 2, Reachability: 1)";
+    assert_eq!(from_spec(nested).unwrap().name(), nested);
+
+    // large sums are clamped, but never to the values of a decided game
+    let board = *GameState::from_fen("W3/xx2/4/3B w 4").unwrap().board();
+    let huge = Weighted::new().with(Mobility, 1e12, 1e12);
+    assert!(Mobility.eval(player, &board) < 0);
+    assert_eq!(huge.eval(player, &board), MIN_VAL + 1);
+    assert_eq!(huge.eval(player.enemy(), &board), MAX_VAL - 1);
+}
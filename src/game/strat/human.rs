@@ -0,0 +1,128 @@
+use super::*;
+use std::io::{self, Write};
+
+const HELP: &str = "\
+Enter a move as a6/a5/a6 or a7-a6/a7 (community notation, ranks counted from 1), or one of
+  hint    suggest a move
+  undo    take back your last move
+  resign  give up the game
+  board   show the board again
+  help    show this message";
+
+/// A person playing from the terminal, who gets shown the board and types in moves.
+pub struct Human {
+    /// Suggests moves when asked for a hint.
+    hint: Box<dyn Strategy>,
+}
+
+impl Human {
+    pub fn new() -> Self {
+        Self::with_hint(Box::new(LookAhead::new()))
+    }
+    pub fn with_hint(hint: Box<dyn Strategy>) -> Self {
+        Self { hint }
+    }
+}
+
+impl Default for Human {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Human {
+    fn name(&self) -> String {
+        "Human".into()
+    }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        loop {
+            match self.act(state) {
+                Ok(Action::Move(mov)) => return mov,
+                Ok(_) => println!("You have to play a move here."),
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+    fn act(&mut self, state: &GameState) -> Result<Action, StrategyError> {
+        println!("{}", state.board().display(&BoardStyle::default()));
+        let stdin = io::stdin();
+        let mut line = String::new();
+        loop {
+            print!("{:?} to move> ", state.turn());
+            io::stdout().flush().ok();
+
+            line.clear();
+            match stdin.read_line(&mut line) {
+                Ok(0) => return Err(StrategyError::Crashed("end of input".into())),
+                Ok(_) => {}
+                Err(err) => return Err(StrategyError::Crashed(err.to_string())),
+            }
+            match parse_input(&line, state) {
+                Ok(Input::Action(action)) => return Ok(action),
+                Ok(Input::Hint) => {
+                    let mov = self.hint.find_move(state);
+                    println!("Try {} ({})", mov, mov.community_notation());
+                }
+                Ok(Input::Board) => println!("{}", state.board().display(&BoardStyle::default())),
+                Ok(Input::Help) => println!("{}", HELP),
+                Err(reason) => println!("{}, enter 'help' to see what you can do", reason),
+            }
+        }
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        Box::new(Self::with_hint(self.hint.dup()))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Input {
+    Action(Action),
+    Hint,
+    Board,
+    Help,
+}
+
+/// Reads a line typed in by the player, or explains what is wrong with it.
+fn parse_input(line: &str, state: &GameState) -> Result<Input, String> {
+    let line = line.trim();
+    match line.to_lowercase().as_str() {
+        "" => return Err("Nothing entered".into()),
+        "undo" => return Ok(Input::Action(Action::Undo)),
+        "resign" => return Ok(Input::Action(Action::Resign)),
+        "hint" => return Ok(Input::Hint),
+        "board" => return Ok(Input::Board),
+        "help" | "?" => return Ok(Input::Help),
+        _ => {}
+    }
+    let mov = line
+        .parse::<Move>()
+        .map_err(|err| format!("'{}' is not a move: {}", line, err))?;
+    state
+        .check_move(mov)
+        .map_err(|reason| format!("{} is not allowed: {}", mov, reason))?;
+    Ok(Input::Action(Action::Move(mov)))
+}
+
+#[test]
+fn human_input() {
+    let state = GameState::new();
+    let mov = "a6/a5/a6".parse::<Move>().unwrap();
+    assert_eq!(
+        parse_input(" a6/a5/a6\n", &state),
+        Ok(Input::Action(Action::Move(mov)))
+    );
+    assert_eq!(
+        parse_input("a7-a6/a7", &state),
+        Ok(Input::Action(Action::Move(mov)))
+    );
+    assert_eq!(parse_input("Undo", &state), Ok(Input::Action(Action::Undo)));
+    assert_eq!(parse_input("hint", &state), Ok(Input::Hint));
+
+    let not_mine = parse_input("a3/a4/a5", &state).unwrap_err();
+    assert_eq!(
+        not_mine,
+        "a3/a4/a5 is not allowed: there is no amazon of the player to move on a3"
+    );
+    assert!(parse_input("a6/a5", &state).is_err());
+    assert!(parse_input("", &state).is_err());
+}
@@ -0,0 +1,319 @@
+use super::*;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// How many ticks a [`Budget`] counts between looking at the clock, unless told otherwise.
+const CLOCK_INTERVAL: u64 = 256;
+
+/// How much a search may do before it has to stop.
+#[derive(Clone, Debug)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    nodes: u64,
+    /// How many ticks pass between looking at the clock.
+    clock_interval: u64,
+    exhausted: bool,
+    /// Set by another thread to stop this search, see [`Budget::helper`].
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl Budget {
+    pub fn new(time: Option<Duration>, max_nodes: Option<u64>) -> Self {
+        Self {
+            deadline: time.map(|time| Instant::now() + time),
+            max_nodes,
+            nodes: 0,
+            clock_interval: CLOCK_INTERVAL,
+            exhausted: false,
+            stop: None,
+        }
+    }
+    /// A budget for a thread that helps with a search: it has the same deadline, but runs
+    /// until `stop` is set instead of counting towards the node limit.
+    pub fn helper(&self, stop: &Arc<AtomicBool>) -> Self {
+        Self {
+            deadline: self.deadline,
+            max_nodes: None,
+            nodes: 0,
+            clock_interval: self.clock_interval,
+            exhausted: false,
+            stop: Some(stop.clone()),
+        }
+    }
+    pub fn unlimited() -> Self {
+        Self::new(None, None)
+    }
+    /// Looks at the clock every `ticks` ticks instead, for searches whose ticks take long,
+    /// like whole playouts.
+    pub fn clock_interval(mut self, ticks: u64) -> Self {
+        self.clock_interval = ticks.max(1);
+        self
+    }
+    /// Counts a visited position and tells whether the search has to stop.
+    #[inline]
+    pub fn tick(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.exhausted {
+            // looking at the clock is slow compared to visiting a position
+            self.exhausted = self.max_nodes.is_some_and(|max| self.nodes > max)
+                || (self.nodes.is_multiple_of(self.clock_interval)
+                    && self
+                        .deadline
+                        .is_some_and(|deadline| Instant::now() >= deadline))
+                || self.stop.as_ref().is_some_and(|stop| stop.load(Relaxed));
+        }
+        self.exhausted
+    }
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+    /// The number of positions visited so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+}
+
+/// A strategy that searches a given number of plies deep, so it can be deepened iteratively.
+pub trait DepthSearch: Strategy {
+    /// The depth the strategy searches when used on its own.
+    fn depth(&self) -> usize;
+    /// Searches `depth` plies deep and returns the best move and its value for the player to move,
+    /// or `None` if the budget ran out first.
+    fn search(
+        &mut self,
+        state: &GameState,
+        depth: usize,
+        budget: &mut Budget,
+    ) -> Option<(Move, Value)>;
+    /// Whether a value returned by `search` proves how the game ends, so that searching deeper
+    /// can't change the move. Nothing is proven unless the strategy says so.
+    fn is_proven(&self, _value: Value) -> bool {
+        false
+    }
+}
+
+/// Searches one ply deeper after another until the budget runs out and plays the best move
+/// of the deepest completed search. The depth of the wrapped strategy is the maximum depth.
+#[derive(Clone)]
+pub struct Iterative<S> {
+    search: S,
+    time: Option<Duration>,
+    nodes: Option<u64>,
+    /// The time limit set through [`Strategy::set_time_limit`].
+    time_limit: Option<Duration>,
+}
+
+impl<S: DepthSearch> Iterative<S> {
+    pub fn new(search: S) -> Self {
+        Self {
+            search,
+            time: None,
+            nodes: None,
+            time_limit: None,
+        }
+    }
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+    pub fn with_nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+}
+
+impl<S: DepthSearch + Clone + 'static> Strategy for Iterative<S> {
+    fn name(&self) -> String {
+        let mut args = vec![self.search.name()];
+        if let Some(time) = self.time {
+            args.push(format_time(time));
+        }
+        if let Some(nodes) = self.nodes {
+            args.push(format!("{} nodes", nodes));
+        }
+        format!("Iterative({})", args.join(", "))
+    }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        let time = match (self.time, self.time_limit) {
+            (Some(time), Some(limit)) => Some(time.min(limit)),
+            (time, limit) => time.or(limit),
+        };
+        let mut budget = Budget::new(time, self.nodes);
+        let mut best = None;
+        for depth in 1..=self.search.depth().max(1) {
+            match self.search.search(state, depth, &mut budget) {
+                Some((mov, value)) => {
+                    best = Some(mov);
+                    // searching deeper won't change a decided game
+                    if self.search.is_proven(value) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        // not even the first search finished, but one ply is cheap enough to search in any case
+        best.or_else(|| {
+            self.search
+                .search(state, 1, &mut Budget::unlimited())
+                .map(|(mov, _)| mov)
+        })
+        .unwrap()
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        Box::new(self.clone())
+    }
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+    fn set_threads(&mut self, threads: usize) {
+        self.search.set_threads(threads);
+    }
+}
+
+/// Formats a budget time like `2s` or `500ms`, the way `from_spec` reads it.
+pub(super) fn format_time(time: Duration) -> String {
+    match time.subsec_millis() {
+        0 => format!("{}s", time.as_secs()),
+        _ => format!("{}ms", time.as_millis()),
+    }
+}
+
+/// Parses a time in the format of [`format_time`].
+pub(super) fn parse_time(time: &str) -> Option<Duration> {
+    if let Some(millis) = time.strip_suffix("ms") {
+        millis.parse().ok().map(Duration::from_millis)
+    } else {
+        let secs = time.strip_suffix('s')?;
+        secs.parse().ok().map(Duration::from_secs)
+    }
+}
+
+/// Parses the arguments of `Iterative(strategy[, budget])`, where the budget is a time like
+/// `2s` or `500ms` or a node count like `10000 nodes`, with whitespace already removed.
+pub(super) fn from_spec(spec: &str) -> Result<Box<dyn Strategy>, String> {
+    let (search, budget) = match spec.rsplit_once(',') {
+        Some((search, budget)) if !budget.contains(')') => (search, Some(budget)),
+        _ => (spec, None),
+    };
+    fn iterative<S: DepthSearch + Clone + 'static>(
+        mut search: S,
+        threads: Option<usize>,
+        budget: Option<&str>,
+    ) -> Result<Box<dyn Strategy>, String> {
+        if let Some(threads) = threads {
+            search.set_threads(threads);
+        }
+        let iterative = Iterative::new(search);
+        let number = |n: &str| {
+            n.parse::<u64>()
+                .map_err(|_| format!("'{}' is not a number", n))
+        };
+        Ok(Box::new(match budget {
+            None => iterative,
+            Some(budget) => {
+                if let Some(n) = budget.strip_suffix("nodes") {
+                    iterative.with_nodes(number(n)?)
+                } else if let Some(time) = parse_time(budget) {
+                    iterative.with_time(time)
+                } else {
+                    return Err(format!(
+                        "'{}' is neither a time like 2s or 500ms nor like 10000 nodes",
+                        budget
+                    ));
+                }
+            }
+        }))
+    }
+
+    let (search, threads) = split_threads(search)?;
+    let (name, args, heuristic) = parse_call(&search)?;
+    let heuristic = heuristic.map(heuristic::from_spec).transpose()?;
+    let h = || heuristic.clone().unwrap_or_else(|| Arc::new(Reachability));
+    match (name, &args[..], &heuristic) {
+        ("minimax", &[depth], _) => iterative(
+            Minimax::with_depth(depth).with_heuristic(h()),
+            threads,
+            budget,
+        ),
+        ("alphabeta", &[depth], _) => iterative(
+            AlphaBeta::with_depth(depth).with_heuristic(h()),
+            threads,
+            budget,
+        ),
+        ("deepidiot", &[depth], _) => iterative(
+            DeepIdiot::with_depth(depth).with_heuristic(h()),
+            threads,
+            budget,
+        ),
+        ("deepidiot", &[depth, width, memory], _) => iterative(
+            DeepIdiot::checked(depth, width, memory)?.with_heuristic(h()),
+            threads,
+            budget,
+        ),
+        _ => Err(format!(
+            "'{}' can't be deepened, known are: Minimax(depth[, heuristic]), AlphaBeta(depth[, heuristic]), \
+             DeepIdiot(depth[, width, memory][, heuristic])",
+            search
+        )),
+    }
+}
+
+#[test]
+fn iterative_budgets() {
+    let state = GameState::with_size(6);
+    let mut by_nodes = Iterative::new(AlphaBeta::with_depth(20)).with_nodes(2000);
+    assert_eq!(
+        by_nodes.name(),
+        "Iterative(AlphaBeta(20, Reachability), 2000 nodes)"
+    );
+    let mov = by_nodes.find_move(&state);
+    assert_eq!(by_nodes.find_move(&state), mov);
+    assert!(state.check_move(mov).is_ok());
+
+    let mut by_time = super::from_spec("Iterative(AlphaBeta(20), 200ms)").unwrap();
+    assert_eq!(
+        by_time.name(),
+        "Iterative(AlphaBeta(20, Reachability), 200ms)"
+    );
+    let start = Instant::now();
+    assert!(state.check_move(by_time.find_move(&state)).is_ok());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // a won position is not searched any deeper
+    let won = GameState::from_fen("Bx2/1x2/4/3W w 4").unwrap();
+    let mut budget = Budget::unlimited();
+    let mut alpha_beta = AlphaBeta::with_depth(1);
+    let (_, value) = alpha_beta.search(&won, 1, &mut budget).unwrap();
+    assert!(alpha_beta.is_proven(value));
+    // the values of other searches don't prove anything, even the largest
+    let mut minimax = Minimax::with_depth(1);
+    let (_, value) = minimax.search(&won, 1, &mut budget).unwrap();
+    assert_eq!(value, MAX_VAL);
+    assert!(!minimax.is_proven(value));
+
+    // DeepIdiot plays the first move of the line its value belongs to, so it can be deepened too,
+    // even when the game ends within the search
+    let trap = GameState::from_fen("Bx2/x3/4/3W w 4").unwrap();
+    let mut deep_idiot = super::from_spec("Iterative(DeepIdiot(3, 100, 10), 2000 nodes)").unwrap();
+    assert_eq!(
+        deep_idiot.name(),
+        "Iterative(DeepIdiot(3, 100, 10, Reachability), 2000 nodes)"
+    );
+    for _ in 0..10 {
+        assert!(trap.legal_move(deep_idiot.find_move(&trap)));
+    }
+
+    // without the budget for a single ply, that ply is still searched
+    let mut starved = Iterative::new(AlphaBeta::with_depth(20)).with_nodes(1);
+    assert_eq!(
+        starved.find_move(&state),
+        AlphaBeta::with_depth(1).find_move(&state)
+    );
+}
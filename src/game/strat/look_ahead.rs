@@ -1,23 +1,47 @@
 use super::*;
 
+/// Plays the best of a thousand random moves.
 #[derive(Copy, Clone)]
-pub struct LookAhead;
+pub struct LookAhead<H = Reachability> {
+    heuristic: H,
+}
+
+impl LookAhead {
+    pub fn new() -> Self {
+        Self {
+            heuristic: Reachability,
+        }
+    }
+}
+
+impl Default for LookAhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> LookAhead<H> {
+    /// Judges the moves with `heuristic` instead.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: I) -> LookAhead<I> {
+        LookAhead { heuristic }
+    }
+}
 
-impl Strategy for LookAhead {
+impl<H: Heuristic + Clone + Send + 'static> Strategy for LookAhead<H> {
     fn name(&self) -> String {
-        "LookAhead".into()
+        format!("LookAhead({})", self.heuristic.name())
     }
     fn find_move(&mut self, state: &GameState) -> Move {
         let mut state = state.clone();
 
         let mut best_value = 0;
         let mut best_move = Random.find_move(&state);
-        let me = state.turn;
+        let me = state.turn();
 
         for _ in 0..1000 {
             let mov = Random.find_move(&state);
             state.do_move(mov);
-            let value = Reachability.eval(me, &state.board);
+            let value = self.heuristic.eval(me, state.board());
             state.undo_move(mov);
             if value > best_value {
                 best_value = value;
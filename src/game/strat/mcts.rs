@@ -0,0 +1,438 @@
+use super::*;
+use rand::{prelude::*, rngs::StdRng};
+use std::{collections::HashMap, sync::Arc, thread};
+
+const DEFAULT_ITERATIONS: u64 = 10_000;
+/// The exploration constant of UCT that balances the terms for a win rate in 0..=1.
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// The heuristic value at which a playout cut off early counts as about 73% won.
+const EVAL_SCALE: f64 = 10.0;
+/// How many random moves a heuristic playout picks the best of.
+const PLAYOUT_SAMPLES: usize = 4;
+
+/// How the moves of a playout are chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Playout {
+    Random,
+    /// The best of a few random moves by [`move_score`].
+    Heuristic,
+}
+
+/// Monte Carlo tree search, which grows a tree of the moves with the best playout results,
+/// selected with UCT, and plays the move that was visited most.
+///
+/// With more than one thread, every thread grows its own tree with its own random numbers
+/// and the iterations split between them, and the visits of the root moves are added up.
+#[derive(Copy, Clone)]
+pub struct Mcts<H = Reachability> {
+    iterations: Option<u64>,
+    time: Option<Duration>,
+    /// The time limit set through [`Strategy::set_time_limit`].
+    time_limit: Option<Duration>,
+    exploration: f64,
+    playout: Playout,
+    /// The plies after which a playout is stopped and the position evaluated instead.
+    cutoff: Option<usize>,
+    /// Evaluates the positions where playouts are cut off.
+    heuristic: H,
+    seed: u64,
+    threads: usize,
+}
+
+impl Mcts {
+    pub fn new() -> Self {
+        Self {
+            iterations: Some(DEFAULT_ITERATIONS),
+            time: None,
+            time_limit: None,
+            exploration: DEFAULT_EXPLORATION,
+            playout: Playout::Random,
+            cutoff: None,
+            heuristic: Reachability,
+            seed: 0,
+            threads: 1,
+        }
+    }
+    pub fn with_iterations(iterations: u64) -> Self {
+        Self {
+            iterations: Some(iterations),
+            ..Self::new()
+        }
+    }
+    /// Searches for `time` each move, and stops after the iterations only if they are set too.
+    pub fn with_time(time: Duration) -> Self {
+        Self {
+            iterations: None,
+            time: Some(time),
+            ..Self::new()
+        }
+    }
+}
+
+impl<H> Mcts<H> {
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+    pub fn playout(mut self, playout: Playout) -> Self {
+        self.playout = playout;
+        self
+    }
+    pub fn cutoff(mut self, plies: usize) -> Self {
+        self.cutoff = Some(plies);
+        self
+    }
+    pub fn iterations(mut self, iterations: u64) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+    /// The seed of the random numbers, which are the same for the same position and seed.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    /// The same search, but evaluating the positions at the cutoff with `heuristic`.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: I) -> Mcts<I> {
+        Mcts {
+            iterations: self.iterations,
+            time: self.time,
+            time_limit: self.time_limit,
+            exploration: self.exploration,
+            playout: self.playout,
+            cutoff: self.cutoff,
+            heuristic,
+            seed: self.seed,
+            threads: self.threads,
+        }
+    }
+}
+
+impl Default for Mcts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Node {
+    /// The move from the parent, `None` for the root.
+    mov: Option<Move>,
+    parent: usize,
+    children: Vec<usize>,
+    /// The moves without a child yet, `None` until the node is expanded the first time.
+    untried: Option<Vec<Move>>,
+    visits: u32,
+    /// The sum of the playout results for the player who played `mov`.
+    score: f64,
+}
+
+impl Node {
+    fn new(mov: Option<Move>, parent: usize) -> Self {
+        Self {
+            mov,
+            parent,
+            children: Vec::new(),
+            untried: None,
+            visits: 0,
+            score: 0.0,
+        }
+    }
+}
+
+impl<H: Heuristic> Mcts<H> {
+    /// Runs the search and returns every move of the root with its number of visits.
+    pub(super) fn search(&self, state: &GameState, budget: &mut Budget) -> Vec<(Move, u32)> {
+        let mut rng = StdRng::seed_from_u64(self.seed ^ state.hash());
+        let mut nodes = vec![Node::new(None, 0)];
+
+        while !budget.tick() {
+            let mut state = state.clone();
+            let mut node = 0;
+
+            // selection and expansion
+            let result = loop {
+                let untried = nodes[node].untried.get_or_insert_with(|| {
+                    let mut moves = state.moves().collect::<Vec<_>>();
+                    moves.shuffle(&mut rng);
+                    moves
+                });
+                if let Some(mov) = untried.pop() {
+                    state.do_move(mov);
+                    nodes.push(Node::new(Some(mov), node));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    node = child;
+                    break self.play_out(&mut state, &mut rng);
+                }
+                if nodes[node].children.is_empty() {
+                    // the player to move has lost
+                    break 0.0;
+                }
+                let parent = &nodes[node];
+                let log_visits = (parent.visits.max(1) as f64).ln();
+                node = *parent
+                    .children
+                    .iter()
+                    .max_by(|&&a, &&b| {
+                        let uct = |child: &Node| {
+                            let visits = child.visits.max(1) as f64;
+                            child.score / visits + self.exploration * (log_visits / visits).sqrt()
+                        };
+                        uct(&nodes[a]).total_cmp(&uct(&nodes[b]))
+                    })
+                    .unwrap();
+                state.do_move(nodes[node].mov.unwrap());
+            };
+
+            // backpropagation, with the result for the player to move in the last node
+            let mut score = 1.0 - result;
+            loop {
+                nodes[node].visits += 1;
+                nodes[node].score += score;
+                if node == 0 {
+                    break;
+                }
+                score = 1.0 - score;
+                node = nodes[node].parent;
+            }
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .map(|&child| (nodes[child].mov.unwrap(), nodes[child].visits))
+            .collect()
+    }
+
+    /// Plays the game on and returns the result for the player to move, 1 for a win.
+    fn play_out(&self, state: &mut GameState, rng: &mut StdRng) -> f64 {
+        let player = state.turn();
+        let mut plies = 0;
+        let (turn, result) = loop {
+            if self.cutoff.is_some_and(|cutoff| plies >= cutoff) {
+                let eval = self.heuristic.eval(state.turn(), state.board()) as f64;
+                break (state.turn(), 1.0 / (1.0 + (-eval / EVAL_SCALE).exp()));
+            }
+            let mov = match self.playout {
+                Playout::Random => random_move(state, rng),
+                Playout::Heuristic => (0..PLAYOUT_SAMPLES)
+                    .filter_map(|_| random_move(state, rng))
+                    .max_by_key(|&mov| move_score(state, mov)),
+            };
+            match mov {
+                Some(mov) => state.do_move(mov),
+                None => break (state.turn(), 0.0),
+            }
+            plies += 1;
+        };
+        if turn == player {
+            result
+        } else {
+            1.0 - result
+        }
+    }
+}
+
+impl<H: Heuristic + Clone + Send + 'static> Strategy for Mcts<H> {
+    fn name(&self) -> String {
+        let mut args = Vec::new();
+        if let Some(iterations) = self.iterations {
+            args.push(iterations.to_string());
+        }
+        if let Some(time) = self.time {
+            args.push(iterative::format_time(time));
+        }
+        if let Some(cutoff) = self.cutoff {
+            args.push(format!("cutoff {}", cutoff));
+            args.push(self.heuristic.name());
+        }
+        if self.playout == Playout::Heuristic {
+            args.push("heuristic playouts".into());
+        }
+        if self.exploration != DEFAULT_EXPLORATION {
+            args.push(format!("c = {}", self.exploration));
+        }
+        if self.seed != 0 {
+            args.push(format!("seed {}", self.seed));
+        }
+        if self.threads > 1 {
+            args.push(format!("{} threads", self.threads));
+        }
+        format!("Mcts({})", args.join(", "))
+    }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        let time = match (self.time, self.time_limit) {
+            (Some(time), Some(limit)) => Some(time.min(limit)),
+            (time, limit) => time.or(limit),
+        };
+        // a playout takes long enough to look at the clock after every one
+        let budget = |iterations| Budget::new(time, iterations).clock_interval(1);
+        let mut visits = if self.threads == 1 {
+            self.search(state, &mut budget(self.iterations))
+        } else {
+            thread::scope(|scope| {
+                let threads = (0..self.threads as u64)
+                    .map(|i| {
+                        let mcts = Self {
+                            seed: self.seed.wrapping_add(i),
+                            ..self.clone()
+                        };
+                        let iterations = self.iterations.map(|iterations| {
+                            iterations / self.threads as u64
+                                + (i < iterations % self.threads as u64) as u64
+                        });
+                        scope.spawn(move || mcts.search(state, &mut budget(iterations)))
+                    })
+                    .collect::<Vec<_>>();
+                // added up in the order the threads found the moves, so equal visits are decided the same way
+                let mut visits = Vec::new();
+                let mut index = HashMap::new();
+                for (mov, n) in threads
+                    .into_iter()
+                    .flat_map(|thread| thread.join().unwrap())
+                {
+                    let i = *index.entry(mov).or_insert_with(|| {
+                        visits.push((mov, 0));
+                        visits.len() - 1
+                    });
+                    visits[i].1 += n;
+                }
+                visits
+            })
+        };
+        // the first of the most visited moves
+        visits.reverse();
+        visits
+            .into_iter()
+            .max_by_key(|&(_, visits)| visits)
+            .map_or_else(|| state.moves().next().unwrap(), |(mov, _)| mov)
+    }
+    fn dup(&self) -> Box<dyn Strategy> {
+        Box::new(self.clone())
+    }
+    fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+    }
+    fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+}
+
+/// Parses the arguments of `Mcts(...)` in the format of its name, with whitespace already
+/// removed, like `1000,2s,cutoff8,territory,heuristicplayouts,c=1.2,seed3`.
+pub(super) fn from_spec(args: &str) -> Result<Mcts<SharedHeuristic>, String> {
+    let number = |n: &str| {
+        n.parse::<u64>()
+            .map_err(|_| format!("'{}' is not a number", n))
+    };
+    let mut mcts = Mcts::new().with_heuristic(Arc::new(Reachability) as SharedHeuristic);
+    let (mut iterations, mut time) = (None, None);
+    for arg in split_args(args) {
+        if let Ok(n) = arg.parse::<u64>() {
+            if iterations.replace(n).is_some() {
+                return Err(format!("more than one number of iterations in '{}'", args));
+            }
+        } else if let Some(t) = iterative::parse_time(arg) {
+            time = Some(t);
+        } else if let Some(plies) = arg.strip_prefix("cutoff") {
+            mcts = mcts.cutoff(number(plies)? as usize);
+        } else if let Some(seed) = arg.strip_prefix("seed") {
+            mcts = mcts.seed(number(seed)?);
+        } else if let Some(c) = arg.strip_prefix("c=") {
+            let c = c
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' is not an exploration constant", c))?;
+            mcts = mcts.exploration(c);
+        } else if arg == "heuristicplayouts" {
+            mcts = mcts.playout(Playout::Heuristic);
+        } else {
+            mcts = mcts.with_heuristic(heuristic::from_spec(arg)?);
+        }
+    }
+    if iterations.is_some() || time.is_some() {
+        mcts.iterations = iterations;
+        mcts.time = time;
+    }
+    Ok(mcts)
+}
+
+#[test]
+fn mcts_finds_the_trap() {
+    // shooting at a1 traps the black amazon in the corner
+    let state = GameState::from_fen("Bx2/1x2/4/3W w 4").unwrap();
+    for playout in [Playout::Random, Playout::Heuristic] {
+        let mut mcts = Mcts::with_iterations(2000).playout(playout);
+        let mov = mcts.find_move(&state);
+        assert_eq!(mov.arrow, Pos::from((0, 1)), "{}", mcts.name());
+    }
+    let mut threaded = super::from_spec("Mcts(2000, 4 threads)").unwrap();
+    assert_eq!(threaded.name(), "Mcts(2000, 4 threads)");
+    assert_eq!(threaded.find_move(&state).arrow, Pos::from((0, 1)));
+}
+
+#[test]
+fn mcts_is_reproducible() {
+    let state = GameState::with_size(6);
+    let mut mcts = Mcts::with_iterations(300).cutoff(4).seed(7);
+    assert_eq!(mcts.name(), "Mcts(300, cutoff 4, Reachability, seed 7)");
+    let mov = mcts.find_move(&state);
+    assert_eq!(mcts.find_move(&state), mov);
+    assert!(state.check_move(mov).is_ok());
+}
+
+#[test]
+fn mcts_budgets() {
+    let state = GameState::with_size(6);
+    let mut budget = Budget::new(None, Some(50));
+    let visits = Mcts::new().search(&state, &mut budget);
+    assert_eq!(visits.iter().map(|&(_, n)| n).sum::<u32>(), 50);
+
+    let mut timed = super::from_spec("Mcts(1000000, cutoff 8)").unwrap();
+    timed.set_time_limit(Some(Duration::from_millis(200)));
+    let start = std::time::Instant::now();
+    assert!(state.check_move(timed.find_move(&state)).is_ok());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // heuristic playouts are slow, but the time is still kept well within the grace of engines
+    let mut slow = super::from_spec("Mcts(1000000, heuristic playouts)").unwrap();
+    slow.set_time_limit(Some(Duration::from_millis(100)));
+    let state = GameState::new();
+    let start = std::time::Instant::now();
+    assert!(state.check_move(slow.find_move(&state)).is_ok());
+    assert!(start.elapsed() < Duration::from_millis(400));
+}
+
+#[test]
+fn mcts_specs() {
+    for mcts in [
+        Mcts::new(),
+        Mcts::with_time(Duration::from_secs(2)),
+        Mcts::with_time(Duration::from_millis(500)).cutoff(4),
+        Mcts::with_time(Duration::from_secs(1)).iterations(300),
+        Mcts::with_iterations(300)
+            .exploration(0.5)
+            .playout(Playout::Heuristic)
+            .seed(3),
+    ] {
+        let name = mcts.name();
+        assert_eq!(super::from_spec(&name).unwrap().name(), name);
+    }
+    let spec = "Mcts(300, cutoff 6, Weighted(Mobility: 1..0, Territory: 1), 2 threads)";
+    assert_eq!(super::from_spec(spec).unwrap().name(), spec);
+    assert_eq!(super::from_spec("mcts()").unwrap().name(), "Mcts(10000)");
+
+    // the heuristic evaluates the position at the cutoff
+    let state = GameState::from_fen("Bx2/1x2/4/3W w 4").unwrap();
+    let mut territory = Mcts::with_iterations(500)
+        .cutoff(1)
+        .with_heuristic(Territory);
+    assert_eq!(territory.find_move(&state).arrow, Pos::from((0, 1)));
+
+    for spec in [
+        "Mcts(300, 400)",
+        "Mcts(cutoff x)",
+        "Mcts(c = x)",
+        "Mcts(300, Nonsense)",
+    ] {
+        assert!(super::from_spec(spec).is_err(), "{}", spec);
+    }
+}
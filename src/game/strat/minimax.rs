@@ -1,24 +1,43 @@
 use super::*;
 
 #[derive(Copy, Clone)]
-pub struct Minimax {
+pub struct Minimax<H = Reachability> {
     depth: usize,
+    heuristic: H,
 }
 
 impl Minimax {
     pub fn with_depth(depth: usize) -> Self {
-        Self { depth }
+        Self {
+            depth,
+            heuristic: Reachability,
+        }
+    }
+}
+
+impl<H> Minimax<H> {
+    /// The same search, but evaluating the positions with `heuristic`.
+    pub fn with_heuristic<I: Heuristic>(self, heuristic: I) -> Minimax<I> {
+        Minimax {
+            depth: self.depth,
+            heuristic,
+        }
     }
 }
 
 fn alpha_beta(
+    heuristic: &impl Heuristic,
     state: &mut GameState,
     mut alpha: i32,
     beta: i32,
     depth: usize,
+    budget: &mut Budget,
 ) -> (i32, Option<Move>) {
+    if budget.tick() {
+        return (0, None);
+    }
     if depth == 0 {
-        return (Reachability.eval(state.turn, &state.board), None);
+        return (heuristic.eval(state.turn(), state.board()), None);
     }
 
     if state.is_finished() {
@@ -31,7 +50,7 @@ fn alpha_beta(
         let mov = Random.find_move(state);
 
         state.do_move(mov);
-        let score = -alpha_beta(state, -beta, -alpha, depth - 1).0;
+        let score = -alpha_beta(heuristic, state, -beta, -alpha, depth - 1, budget).0;
         state.undo_move(mov);
 
         if score >= beta {
@@ -41,20 +60,47 @@ fn alpha_beta(
             alpha = score;
             best = Some(mov);
         }
+        // even if every move loses, one has to be played
+        best = best.or(Some(mov));
     }
     (alpha, best)
 }
 
-impl Strategy for Minimax {
+impl<H: Heuristic + Clone + Send + 'static> Strategy for Minimax<H> {
     fn name(&self) -> String {
-        format!("Minimax({})", self.depth)
+        format!("Minimax({}, {})", self.depth, self.heuristic.name())
     }
     fn find_move(&mut self, state: &GameState) -> Move {
-        alpha_beta(&mut state.clone(), -i32::MAX, i32::MAX, self.depth)
-            .1
+        self.search(state, self.depth, &mut Budget::unlimited())
             .unwrap()
+            .0
     }
     fn dup(&self) -> Box<dyn Strategy> {
         Box::new(self.clone())
     }
 }
+
+impl<H: Heuristic + Clone + Send + 'static> DepthSearch for Minimax<H> {
+    fn depth(&self) -> usize {
+        self.depth
+    }
+    fn search(
+        &mut self,
+        state: &GameState,
+        depth: usize,
+        budget: &mut Budget,
+    ) -> Option<(Move, Value)> {
+        let (value, mov) = alpha_beta(
+            &self.heuristic,
+            &mut state.clone(),
+            -i32::MAX,
+            i32::MAX,
+            depth,
+            budget,
+        );
+        if budget.exhausted() {
+            return None;
+        }
+        mov.map(|mov| (mov, value))
+    }
+}
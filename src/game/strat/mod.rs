@@ -1,16 +1,197 @@
 use super::*;
+use std::{error, fmt, sync::Arc, time::Duration};
 
+pub mod alpha_beta;
 pub mod di;
+pub mod external;
 pub mod heuristic;
+pub mod human;
+pub mod iterative;
 pub mod look_ahead;
+pub mod mcts;
 pub mod minimax;
 pub mod snail;
+pub mod table;
 pub mod true_random;
 
-pub use {di::*, look_ahead::*, minimax::*, snail::*, true_random::*, heuristic::*};
+pub use {
+    alpha_beta::*, di::*, external::*, heuristic::*, human::*, iterative::*, look_ahead::*,
+    mcts::*, minimax::*, snail::*, table::*, true_random::*,
+};
 
-pub trait Strategy {
+/// How a player chooses its moves. Strategies are `Send`, so games can be played on other threads.
+pub trait Strategy: Send {
     fn name(&self) -> String;
     fn find_move(&mut self, board: &GameState) -> Move;
     fn dup(&self) -> Box<dyn Strategy>;
+    /// Decides what to do in the position, which is usually to play the move of `find_move`.
+    /// Strategies can also give up or take back moves, or fail, e.g. because they depend on another process.
+    fn act(&mut self, state: &GameState) -> Result<Action, StrategyError> {
+        Ok(Action::Move(self.find_move(state)))
+    }
+    /// Tells the strategy how long it may think about each move, `None` for no limit.
+    /// Strategies that can't control their thinking time ignore it.
+    fn set_time_limit(&mut self, _limit: Option<Duration>) {}
+    /// Tells the strategy how many threads it may search with.
+    /// Strategies that can't search in parallel ignore it.
+    fn set_threads(&mut self, _threads: usize) {}
+}
+
+/// What a player does on its turn, see [`Strategy::act`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Move(Move),
+    Resign,
+    /// Takes back the last move of the player and the reply to it.
+    Undo,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StrategyError {
+    /// No move was found within the given time.
+    Timeout(Duration),
+    /// The strategy can't play anymore, e.g. because its process exited.
+    Crashed(String),
+    /// The strategy answered with something that is not a move.
+    Protocol(String),
+}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout(time) => write!(f, "no answer within {:.1}s", time.as_secs_f64()),
+            Self::Crashed(reason) => write!(f, "crashed: {}", reason),
+            Self::Protocol(reason) => write!(f, "protocol error: {}", reason),
+        }
+    }
+}
+
+impl error::Error for StrategyError {}
+
+/// Creates a strategy from a description like `Minimax(2)`, in the format of [`Strategy::name`].
+/// Case and whitespace are ignored, except in `External(command args...)`.
+/// A last argument like `4 threads` is passed to [`Strategy::set_threads`].
+pub fn from_spec(spec: &str) -> Result<Box<dyn Strategy>, String> {
+    let trimmed = spec.trim();
+    if trimmed
+        .get(..9)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("external("))
+    {
+        if let Some(command) = trimmed[9..].strip_suffix(')') {
+            let mut words = command.split_whitespace();
+            let program = words.next().ok_or("missing command in External()")?;
+            return Ok(Box::new(External::new(program, words)));
+        }
+    }
+
+    let spec = spec
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    if let Some(args) = spec
+        .strip_prefix("iterative(")
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        return iterative::from_spec(args);
+    }
+    let (spec, threads) = split_threads(&spec)?;
+    let mut strategy: Box<dyn Strategy> = match spec
+        .strip_prefix("mcts(")
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        Some(args) => Box::new(mcts::from_spec(args)?),
+        None => from_call(&spec)?,
+    };
+    if let Some(threads) = threads {
+        strategy.set_threads(threads);
+    }
+    Ok(strategy)
+}
+
+/// Creates a strategy from a normalized spec with numeric arguments and maybe a heuristic.
+fn from_call(spec: &str) -> Result<Box<dyn Strategy>, String> {
+    let (name, args, heuristic) = parse_call(spec)?;
+    let heuristic = heuristic.map(heuristic::from_spec).transpose()?;
+    let h = || heuristic.clone().unwrap_or_else(|| Arc::new(Reachability));
+
+    Ok(match (name, &args[..], &heuristic) {
+        ("random" | "truerandom", [], None) => Box::new(Random),
+        ("snail", [], None) => Box::new(Snail),
+        ("randomsnail", [], None) => Box::new(RandomSnail),
+        ("lookahead", [], _) => Box::new(LookAhead::new().with_heuristic(h())),
+        ("human", [], None) => Box::new(Human::new()),
+        ("minimax", &[depth], _) => Box::new(Minimax::with_depth(depth).with_heuristic(h())),
+        ("alphabeta", &[depth], _) => Box::new(AlphaBeta::with_depth(depth).with_heuristic(h())),
+        ("deepidiot", &[depth], _) => Box::new(DeepIdiot::with_depth(depth).with_heuristic(h())),
+        ("deepidiot", &[depth, width, memory], _) => {
+            Box::new(DeepIdiot::checked(depth, width, memory)?.with_heuristic(h()))
+        }
+        _ => {
+            return Err(format!(
+                "unknown strategy '{}', known are: Random, Snail, RandomSnail, \
+                 LookAhead[(heuristic)], Human, Minimax(depth[, heuristic]), \
+                 AlphaBeta(depth[, heuristic][, N threads]), \
+                 DeepIdiot(depth[, width, memory][, heuristic]), \
+                 Mcts([iterations][, time][, cutoff N, heuristic][, heuristic playouts]\
+                 [, c = exploration][, seed N][, N threads]), \
+                 Iterative(strategy[, budget]), External(command args...)",
+                spec
+            ))
+        }
+    })
+}
+
+/// Splits the thread count off a normalized spec like `alphabeta(4,8threads)`.
+fn split_threads(spec: &str) -> Result<(String, Option<usize>), String> {
+    if let Some((call, threads)) = spec
+        .strip_suffix("threads)")
+        .and_then(|rest| rest.rsplit_once(','))
+    {
+        let threads = threads
+            .parse::<usize>()
+            .map_err(|_| format!("'{}' is not a number of threads", threads))?;
+        return Ok((format!("{})", call), Some(threads)));
+    }
+    Ok((spec.to_string(), None))
+}
+
+/// Splits the arguments of a spec at the commas outside of parentheses, dropping empty ones.
+fn split_args(args: &str) -> Vec<&str> {
+    let mut depth = 0;
+    args.split(|c| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        c == ',' && depth == 0
+    })
+    .filter(|arg| !arg.trim().is_empty())
+    .collect()
+}
+
+/// Splits a normalized spec like `deepidiot(3,1000,20,territory)` into its name, numeric arguments
+/// and the heuristic, if the last argument is one.
+fn parse_call(spec: &str) -> Result<(&str, Vec<usize>, Option<&str>), String> {
+    let (name, args) = match spec.split_once('(') {
+        Some((name, args)) => match args.strip_suffix(')') {
+            Some(args) => (name, args),
+            None => return Err(format!("missing ')' in '{}'", spec)),
+        },
+        None => (spec, ""),
+    };
+    let mut args = split_args(args);
+    let heuristic = match args.last() {
+        Some(arg) if arg.starts_with(|c: char| c.is_ascii_alphabetic()) => args.pop(),
+        _ => None,
+    };
+    let args = args
+        .into_iter()
+        .map(|arg| {
+            arg.parse::<usize>()
+                .map_err(|_| format!("'{}' is not a number", arg))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, args, heuristic))
 }
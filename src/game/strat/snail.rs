@@ -15,10 +15,8 @@ macro_rules! __snail__ {
                 $shuffle(&mut amzs);
 
                 for a in amzs {
-                    let mut moves = a
-                        .neighbors()
+                    let mut moves = (a.neighbors(state.board().size()) & state.board().empty())
                         .into_iter()
-                        .filter(|&m| state.board[m].is_empty())
                         .collect::<Vec<_>>();
 
                     $shuffle(&mut moves);
@@ -44,8 +42,8 @@ __snail__!(RandomSnail, shuffle);
 __snail__!(Snail, no_shuffle);
 
 #[inline]
-fn shuffle<T>(v: &mut Vec<T>) {
+fn shuffle<T>(v: &mut [T]) {
     v.shuffle(&mut rand::thread_rng());
 }
 #[inline]
-fn no_shuffle<T>(_: &mut Vec<T>) {}
+fn no_shuffle<T>(_: &mut [T]) {}
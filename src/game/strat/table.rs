@@ -0,0 +1,207 @@
+use super::*;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+/// How a stored value relates to the true value of the position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, the true value is at least this.
+    Lower,
+    /// The search failed low, the true value is at most this.
+    Upper,
+}
+
+/// What a search found out about a position.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The full hash of the position, to tell it apart from others in the same bucket.
+    pub hash: u64,
+    pub depth: u8,
+    pub value: Value,
+    pub bound: Bound,
+    pub best: Option<Move>,
+}
+
+/// A fixed-size cache of search results indexed by [`GameState::hash`].
+/// Each bucket has one slot that keeps the deepest result and one that takes everything else.
+///
+/// The table can be shared between threads without locks: every slot stores the packed entry
+/// next to its hash xor the packed entry, so a slot torn by two threads writing at once
+/// no longer matches any hash and is ignored.
+pub struct TranspositionTable {
+    buckets: Vec<[Slot; 2]>,
+}
+
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Values are stored in this many bits, which is plenty for [`WIN`] and everything below it.
+const VALUE_BITS: u32 = 22;
+/// Set in every stored entry, so an empty slot doesn't look like an entry of the hash 0.
+const OCCUPIED: u64 = 1 << 63;
+
+impl Slot {
+    fn load(&self) -> Option<Entry> {
+        let data = self.data.load(Relaxed);
+        let check = self.check.load(Relaxed);
+        if data & OCCUPIED == 0 {
+            return None;
+        }
+        Some(Entry::unpack(check ^ data, data))
+    }
+
+    fn store(&self, entry: Option<Entry>) {
+        let data = entry.map_or(0, Entry::pack);
+        let hash = entry.map_or(0, |entry| entry.hash);
+        self.check.store(hash ^ data, Relaxed);
+        self.data.store(data, Relaxed);
+    }
+}
+
+impl Entry {
+    fn pack(self) -> u64 {
+        let pos = |p: Pos| (p.x as u64) << 4 | p.y as u64;
+        let best = self.best.map_or(0, |mov| {
+            1 << 24 | pos(mov.from) << 16 | pos(mov.to) << 8 | pos(mov.arrow)
+        });
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let value = (self.value + (1 << (VALUE_BITS - 1))) as u64;
+        OCCUPIED | best << 32 | bound << 30 | (self.depth as u64) << VALUE_BITS | value
+    }
+
+    fn unpack(hash: u64, data: u64) -> Self {
+        let pos = |bits: u64| Pos::from(((bits >> 4 & 0xf) as u8, (bits & 0xf) as u8));
+        let best = data >> 32;
+        Self {
+            hash,
+            depth: (data >> VALUE_BITS) as u8,
+            value: (data & ((1 << VALUE_BITS) - 1)) as Value - (1 << (VALUE_BITS - 1)),
+            bound: match data >> 30 & 3 {
+                0 => Bound::Exact,
+                1 => Bound::Lower,
+                _ => Bound::Upper,
+            },
+            best: (best >> 24 & 1 == 1).then(|| Move {
+                from: pos(best >> 16),
+                to: pos(best >> 8),
+                arrow: pos(best),
+            }),
+        }
+    }
+}
+
+impl TranspositionTable {
+    /// A table with `2^bits` buckets.
+    pub fn new(bits: u32) -> Self {
+        Self {
+            buckets: (0..1 << bits).map(|_| Default::default()).collect(),
+        }
+    }
+
+    #[inline]
+    fn bucket(&self, hash: u64) -> &[Slot; 2] {
+        &self.buckets[hash as usize & (self.buckets.len() - 1)]
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        self.bucket(hash)
+            .iter()
+            .filter_map(Slot::load)
+            .find(|entry| entry.hash == hash)
+    }
+
+    pub fn store(&self, entry: Entry) {
+        let [deepest, recent] = self.bucket(entry.hash);
+        match deepest.load() {
+            Some(old) if old.hash != entry.hash && old.depth > entry.depth => {
+                recent.store(Some(entry))
+            }
+            Some(old) if old.hash != entry.hash => {
+                recent.store(Some(old));
+                deepest.store(Some(entry));
+            }
+            _ => {
+                // a new result for the same position replaces the old one in the other slot too
+                if recent.load().is_some_and(|old| old.hash == entry.hash) {
+                    recent.store(None);
+                }
+                deepest.store(Some(entry))
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for slot in self.buckets.iter_mut().flatten() {
+            *slot = Slot::default();
+        }
+    }
+}
+
+impl Clone for TranspositionTable {
+    fn clone(&self) -> Self {
+        Self {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|bucket| {
+                    bucket.each_ref().map(|slot| Slot {
+                        check: AtomicU64::new(slot.check.load(Relaxed)),
+                        data: AtomicU64::new(slot.data.load(Relaxed)),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[test]
+fn table_replacement() {
+    let table = TranspositionTable::new(4);
+    let entry = |hash: u64, depth: u8| Entry {
+        hash,
+        depth,
+        value: depth as Value,
+        bound: Bound::Exact,
+        best: None,
+    };
+    // all of these share a bucket
+    table.store(entry(0x10, 3));
+    table.store(entry(0x20, 1));
+    assert_eq!(table.probe(0x10), Some(entry(0x10, 3)));
+    assert_eq!(table.probe(0x20), Some(entry(0x20, 1)));
+
+    table.store(entry(0x30, 2));
+    assert_eq!(table.probe(0x20), None);
+    // a deeper result pushes the old deepest one into the other slot
+    table.store(entry(0x40, 5));
+    assert_eq!(table.probe(0x40), Some(entry(0x40, 5)));
+    assert_eq!(table.probe(0x10), Some(entry(0x10, 3)));
+    assert_eq!(table.probe(0x30), None);
+
+    // the same position is always updated
+    table.store(entry(0x40, 1));
+    assert_eq!(table.probe(0x40), Some(entry(0x40, 1)));
+    assert_eq!(table.probe(0x50), None);
+
+    // everything an entry holds survives packing
+    let full = Entry {
+        hash: 0x60,
+        depth: 200,
+        value: -WIN,
+        bound: Bound::Upper,
+        best: Some(Move {
+            from: Pos::from((11, 0)),
+            to: Pos::from((3, 11)),
+            arrow: Pos::from((0, 7)),
+        }),
+    };
+    table.store(full);
+    assert_eq!(table.probe(0x60), Some(full));
+}
@@ -8,33 +8,32 @@ impl Strategy for Random {
     fn name(&self) -> String {
         "True Random".into()
     }
-    fn find_move(&mut self, board: &GameState) -> Move {
-        let mut rng = rand::thread_rng();
-        let amazons = board.find_movable_amazons();
-        let amazon = amazons[rng.gen_range(0..amazons.len())];
-
-        let mut board = board.board.clone();
-
-        let mut sample_rand = |board: &Board, pos| {
-            let targets = board
-                .reachable_from(pos)
-                .into_iter()
-                .flat_map(|t| pos.to(t))
-                .collect::<Vec<_>>();
-            targets[rng.gen_range(0..targets.len())]
-        };
-
-        let target = sample_rand(&board, amazon);
-        board[amazon] = Slot::Empty;
-        let arrow = sample_rand(&board, target);
-
-        Move {
-            from: amazon,
-            to: target,
-            arrow,
-        }
+    fn find_move(&mut self, state: &GameState) -> Move {
+        random_move(state, &mut rand::thread_rng()).unwrap()
     }
     fn dup(&self) -> Box<dyn Strategy> {
-        Box::new(self.clone())
+        Box::new(*self)
     }
 }
+
+/// A random legal move, `None` if there is none.
+/// Picks an amazon that can move, then its destination and then the arrow.
+pub fn random_move(state: &GameState, rng: &mut impl Rng) -> Option<Move> {
+    let empty = state.board().empty();
+    let movable = state
+        .board()
+        .amazons(state.turn())
+        .iter()
+        .filter(|&a| !Bitboard::square(a).queen_moves(empty).is_empty())
+        .collect::<Vec<_>>();
+    let from = *movable.choose(rng)?;
+    let targets = Bitboard::square(from).queen_moves(empty);
+    let to = targets
+        .iter()
+        .nth(rng.gen_range(0..targets.count() as usize))?;
+    let arrows = Bitboard::square(to).queen_moves(empty | Bitboard::square(from));
+    let arrow = arrows
+        .iter()
+        .nth(rng.gen_range(0..arrows.count() as usize))?;
+    Some(Move { from, to, arrow })
+}
@@ -0,0 +1,60 @@
+//! Random keys for Zobrist hashing: the hash of a position is the xor of a key for every
+//! occupied square and one for the player to move, so moves can update it incrementally.
+
+use super::*;
+
+const SQUARES: usize = MAX_BOARD_LEN * MAX_BOARD_LEN;
+
+/// The keys of white amazons, black amazons and arrows on every square.
+const KEYS: [[u64; SQUARES]; 3] = {
+    let mut keys = [[0; SQUARES]; 3];
+    let mut seed = 0x5EED_A3A2_0115_u64;
+    let mut kind = 0;
+    while kind < 3 {
+        let mut square = 0;
+        while square < SQUARES {
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            keys[kind][square] = split_mix(seed);
+            square += 1;
+        }
+        kind += 1;
+    }
+    keys
+};
+
+/// Hashed in when black is to move.
+pub const BLACK_TO_MOVE: u64 = split_mix(0xB1AC_0000_0000_0001);
+
+/// The finalizer of the SplitMix64 generator, which turns a counter into well mixed bits.
+const fn split_mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// The key of a slot on a square, 0 for an empty one.
+#[inline]
+pub fn key(slot: Slot, p: Pos) -> u64 {
+    let square = p.y as usize * MAX_BOARD_LEN + p.x as usize;
+    match slot {
+        Slot::Empty => 0,
+        Slot::Amazon(player) => KEYS[player as usize][square],
+        Slot::Arrow => KEYS[2][square],
+    }
+}
+
+/// Hashes a position from scratch.
+pub fn hash(board: &Board, turn: Player) -> u64 {
+    let pieces = [
+        (board.amazons(Player::White), Slot::Amazon(Player::White)),
+        (board.amazons(Player::Black), Slot::Amazon(Player::Black)),
+        (board.arrows(), Slot::Arrow),
+    ];
+    let hash = pieces.iter().fold(0, |hash, &(squares, slot)| {
+        squares.iter().fold(hash, |hash, p| hash ^ key(slot, p))
+    });
+    match turn {
+        Player::White => hash,
+        Player::Black => hash ^ BLACK_TO_MOVE,
+    }
+}
@@ -1,33 +1,19 @@
 mod ansi;
 pub mod game;
+pub mod protocol;
+pub mod tui;
+pub mod tune;
 
 pub use game::*;
+use std::{
+    sync::{mpsc, Mutex},
+    thread,
+};
 
-pub fn repeat_games(strats: Vec<Box<dyn Strategy>>, reps: usize, self_games: bool) {
-    let mut wins = vec![vec![(0, 0); strats.len()]; strats.len()];
-
-    for (ai, a) in strats.iter().enumerate() {
-        for (bi, b) in strats.iter().enumerate() {
-            if bi > ai || (bi == ai && !self_games) {
-                continue;
-            }
-            for _ in 0..reps {
-                let winner0 = Game::new(a.dup(), b.dup()).finish();
-                let winner1 = Game::new(b.dup(), a.dup()).finish();
-
-                if winner0 == Player::White {
-                    wins[ai][bi].0 += 1;
-                } else {
-                    wins[bi][ai].1 += 1;
-                }
-                if winner1 == Player::White {
-                    wins[bi][ai].0 += 1;
-                } else {
-                    wins[ai][bi].1 += 1;
-                }
-            }
-        }
-    }
+/// Plays every pairing of the strategies `reps` times with each color and prints the results,
+/// spreading the games over `threads` threads.
+pub fn repeat_games(strats: Vec<Box<dyn Strategy>>, reps: usize, self_games: bool, threads: usize) {
+    let wins = count_wins(&strats, reps, self_games, threads);
 
     for a in 0..strats.len() {
         for b in 0..strats.len() {
@@ -52,3 +38,90 @@ pub fn repeat_games(strats: Vec<Box<dyn Strategy>>, reps: usize, self_games: boo
         }
     }
 }
+
+/// Plays the games of [`repeat_games`] and counts the wins: `wins[a][b]` are the games `a` won
+/// against `b` as white and as black, counted the same no matter which thread played them.
+pub fn count_wins(
+    strats: &[Box<dyn Strategy>],
+    reps: usize,
+    self_games: bool,
+    threads: usize,
+) -> Vec<Vec<(usize, usize)>> {
+    count_wins_from(&[GameState::new()], strats, reps, self_games, threads)
+}
+
+/// [`count_wins`] with the games starting from each of `starts`, e.g. a smaller board
+/// or a few opening moves.
+pub fn count_wins_from(
+    starts: &[GameState],
+    strats: &[Box<dyn Strategy>],
+    reps: usize,
+    self_games: bool,
+    threads: usize,
+) -> Vec<Vec<(usize, usize)>> {
+    // every game with the indices of its white and black player
+    let mut games = Vec::new();
+    for (ai, a) in strats.iter().enumerate() {
+        for (bi, b) in strats.iter().enumerate() {
+            if bi > ai || (bi == ai && !self_games) {
+                continue;
+            }
+            for start in starts {
+                for _ in 0..reps {
+                    games.push((ai, bi, Game::from_state(start.clone(), a.dup(), b.dup())));
+                    games.push((bi, ai, Game::from_state(start.clone(), b.dup(), a.dup())));
+                }
+            }
+        }
+    }
+
+    let games = Mutex::new(games.into_iter());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let sender = sender.clone();
+            let games = &games;
+            scope.spawn(move || loop {
+                let next = games.lock().unwrap().next();
+                match next {
+                    Some((white, black, game)) => {
+                        sender.send((white, black, winner(game))).unwrap()
+                    }
+                    None => break,
+                }
+            });
+        }
+    });
+    drop(sender);
+
+    let mut wins = vec![vec![(0, 0); strats.len()]; strats.len()];
+    for (white, black, winner) in receiver {
+        if winner == Player::White {
+            wins[white][black].0 += 1;
+        } else {
+            wins[black][white].1 += 1;
+        }
+    }
+    wins
+}
+
+/// Plays `game` to the end, a player trying an illegal move forfeits.
+fn winner(mut game: Game) -> Player {
+    game.finish().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        err.player.enemy()
+    })
+}
+
+#[test]
+fn parallel_games() {
+    let strats: Vec<Box<dyn Strategy>> = vec![
+        Box::new(strat::Snail),
+        Box::new(strat::Mcts::with_iterations(4)),
+    ];
+    let sequential = count_wins(&strats, 3, false, 1);
+    assert_eq!(count_wins(&strats, 3, false, 4), sequential);
+    // the pairing is played three times with each color
+    assert_eq!(sequential[0][1].0 + sequential[1][0].1, 3);
+    assert_eq!(sequential[1][0].0 + sequential[0][1].1, 3);
+}
@@ -0,0 +1,321 @@
+//! A line based text protocol modelled after GTP, the Go Text Protocol,
+//! that lets other programs drive a [`Strategy`] running in a separate process.
+//!
+//! Every command is a line `[id] name [args...]` and is answered by `=[id] result`
+//! or `?[id] error`, followed by an empty line. Moves are accepted in both notations
+//! `Move` can be parsed from, and are answered in the notation of its `Display`.
+
+use crate::game::*;
+use std::{
+    io::{self, BufRead, Write},
+    time::Duration,
+};
+
+pub const COMMANDS: &[&str] = &[
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "boardsize",
+    "clear_board",
+    "position",
+    "showposition",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "time_settings",
+    "time_left",
+    "quit",
+];
+
+/// Time settings in the form of GTP's `time_settings`: a main time followed by
+/// periods of `byo_yomi` in which `stones` moves have to be played.
+#[derive(Copy, Clone, Debug, Default)]
+struct Clock {
+    main: Duration,
+    byo_yomi: Duration,
+    stones: u32,
+    /// The time left and the moves left in the current period, as reported by `time_left`.
+    left: [Option<(Duration, u32)>; 2],
+}
+
+impl Clock {
+    /// How long the player to move may think about the current move.
+    fn limit(&self, state: &GameState) -> Option<Duration> {
+        if self.byo_yomi > Duration::ZERO && self.stones == 0 {
+            return None;
+        }
+        let (left, stones) = self.left[state.turn() as usize].unwrap_or((self.main, 0));
+        if left.is_zero() && self.byo_yomi.is_zero() {
+            // neither main time nor byo-yomi are set, so there is no limit
+            return None;
+        }
+        Some(if stones > 0 {
+            left / stones
+        } else {
+            // a rough guess of the number of moves this player still has to make
+            let moves = (state.board().empty().count() / 4).max(1);
+            let byo_yomi = if self.stones > 0 {
+                self.byo_yomi / self.stones
+            } else {
+                Duration::ZERO
+            };
+            left / moves + byo_yomi
+        })
+    }
+}
+
+/// Holds the position and the strategy of one engine process.
+pub struct Engine {
+    strategy: Box<dyn Strategy>,
+    start: GameState,
+    state: GameState,
+    history: Vec<Move>,
+    clock: Clock,
+}
+
+impl Engine {
+    pub fn new(strategy: Box<dyn Strategy>) -> Self {
+        Self {
+            strategy,
+            start: GameState::new(),
+            state: GameState::new(),
+            history: Vec::new(),
+            clock: Clock::default(),
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    fn reset(&mut self, start: GameState) {
+        self.start = start.clone();
+        self.state = start;
+        self.history.clear();
+    }
+
+    /// Runs one command and returns its result or an error message.
+    pub fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        let arg = |i: usize| args.get(i).copied().ok_or("missing argument");
+        let number = |i: usize| -> Result<f64, String> {
+            arg(i)?
+                .parse::<f64>()
+                .ok()
+                .filter(|n| *n >= 0.0)
+                .ok_or_else(|| format!("'{}' is not a non-negative number", args[i]))
+        };
+        let duration = |i: usize| -> Result<Duration, String> {
+            Duration::try_from_secs_f64(number(i)?)
+                .map_err(|_| format!("'{}' is too long a time", args[i]))
+        };
+        match command {
+            "protocol_version" => Ok("2".into()),
+            "name" => Ok(env!("CARGO_PKG_NAME").into()),
+            "version" => Ok(format!(
+                "{} ({})",
+                env!("CARGO_PKG_VERSION"),
+                self.strategy.name()
+            )),
+            "known_command" => Ok(COMMANDS.contains(&arg(0)?).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "boardsize" => {
+                let len = arg(0)?
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|len| (4..=MAX_BOARD_LEN).contains(len))
+                    .ok_or_else(|| {
+                        format!("unacceptable size, has to be in 4..={}", MAX_BOARD_LEN)
+                    })?;
+                self.reset(GameState::with_size(len));
+                Ok(String::new())
+            }
+            "clear_board" => {
+                let len = self.state.board().size();
+                self.reset(if len >= 4 {
+                    GameState::with_size(len)
+                } else {
+                    self.start.clone()
+                });
+                Ok(String::new())
+            }
+            "position" => {
+                let state = GameState::from_fen(&args.join(" ")).map_err(|err| err.to_string())?;
+                self.reset(state);
+                Ok(String::new())
+            }
+            "showposition" => Ok(self.state.to_fen()),
+            "play" => {
+                let (player, mov) = match args {
+                    [mov] => (None, mov),
+                    [player, mov] => (Some(parse_player(player)?), mov),
+                    _ => return Err("expected a move, optionally preceded by the player".into()),
+                };
+                if player.is_some_and(|player| player != self.state.turn()) {
+                    return Err(format!("it is {:?}'s turn", self.state.turn()));
+                }
+                let mov = mov.parse::<Move>().map_err(|err| err.to_string())?;
+                self.state.try_do_move(mov).map_err(|err| err.to_string())?;
+                self.history.push(mov);
+                Ok(String::new())
+            }
+            "genmove" => {
+                if let Some(player) = args.first() {
+                    if parse_player(player)? != self.state.turn() {
+                        return Err(format!("it is {:?}'s turn", self.state.turn()));
+                    }
+                }
+                if self.state.is_finished() {
+                    return Ok("resign".into());
+                }
+                self.strategy.set_time_limit(self.clock.limit(&self.state));
+                let mov = match self.strategy.act(&self.state) {
+                    Ok(Action::Move(mov)) => mov,
+                    Ok(Action::Resign) => return Ok("resign".into()),
+                    Ok(Action::Undo) => {
+                        return Err(format!("{} wants to undo a move", self.strategy.name()))
+                    }
+                    Err(err) => return Err(format!("{} failed: {}", self.strategy.name(), err)),
+                };
+                self.state.try_do_move(mov).map_err(|err| {
+                    format!(
+                        "{} came up with the illegal move {}: {}",
+                        self.strategy.name(),
+                        mov,
+                        err
+                    )
+                })?;
+                self.history.push(mov);
+                Ok(mov.to_string())
+            }
+            "undo" => {
+                let mov = self.history.pop().ok_or("cannot undo")?;
+                self.state.undo_move(mov);
+                Ok(String::new())
+            }
+            "showboard" => Ok(format!("\n{}", plain_board(self.state.board()))),
+            "time_settings" => {
+                self.clock = Clock {
+                    main: duration(0)?,
+                    byo_yomi: duration(1)?,
+                    stones: number(2)? as u32,
+                    left: [None; 2],
+                };
+                Ok(String::new())
+            }
+            "time_left" => {
+                let player = parse_player(arg(0)?)?;
+                self.clock.left[player as usize] = Some((duration(1)?, number(2)? as u32));
+                Ok(String::new())
+            }
+            "quit" => Ok(String::new()),
+            _ => Err("unknown command".into()),
+        }
+    }
+
+    /// Answers commands read from `input` until `quit` or the end of the input.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace().collect::<Vec<_>>();
+            let id = match words.first() {
+                None => continue,
+                Some(word) if word.bytes().all(|b| b.is_ascii_digit()) => {
+                    words.remove(0).to_string()
+                }
+                Some(_) => String::new(),
+            };
+            let (command, args) = match words.split_first() {
+                Some((command, args)) => (*command, args),
+                None => continue,
+            };
+
+            let (status, text) = match self.execute(command, args) {
+                Ok(result) => ('=', result),
+                Err(err) => ('?', err),
+            };
+            let sep = if text.is_empty() || text.starts_with('\n') {
+                ""
+            } else {
+                " "
+            };
+            write!(output, "{}{}{}{}\n\n", status, id, sep, text)?;
+            output.flush()?;
+
+            if command == "quit" {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_player(s: &str) -> Result<Player, String> {
+    match s.to_lowercase().as_str() {
+        "w" | "white" => Ok(Player::White),
+        "b" | "black" => Ok(Player::Black),
+        _ => Err(format!("'{}' is not a player", s)),
+    }
+}
+
+/// Draws the board without colors, with the letters of [`GameState::to_fen`].
+pub fn plain_board(board: &Board) -> String {
+    let len = board.size();
+    let width = (len - 1).to_string().len();
+    let mut buf = format!("{:w$}", "", w = width);
+    for x in 0..len as u8 {
+        buf += &format!(" {}", (b'a' + x) as char);
+    }
+    for y in 0..len as u8 {
+        buf += &format!("\n{:>w$}", y, w = width);
+        for x in 0..len as u8 {
+            buf += match board[(x, y)] {
+                Slot::Empty => " .",
+                Slot::Arrow => " x",
+                Slot::Amazon(Player::White) => " W",
+                Slot::Amazon(Player::Black) => " B",
+            };
+        }
+    }
+    buf
+}
+
+#[test]
+fn protocol_session() {
+    let input = "\
+        1 boardsize 6\n\
+        # a comment\n\
+        2 play w a5-a4/a5\n\
+        3 play b1/b2/b1\n\
+        4 genmove b\n\
+        5 undo\n\
+        6 showposition\n\
+        7 frobnicate\n\
+        8 time_settings inf 0 0\n\
+        9 time_left w 1e400 0\n\
+        10 time_left w 30 0\n\
+        quit\n\
+        11 name\n";
+    let mut output = Vec::new();
+    Engine::new(Box::new(strat::Snail))
+        .run(input.as_bytes(), &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let responses = output.split("\n\n").collect::<Vec<_>>();
+
+    assert_eq!(responses[0], "=1");
+    assert_eq!(responses[1], "=2");
+    assert!(responses[2].starts_with("?3 "), "{}", responses[2]);
+    assert!(responses[3].starts_with("=4 "), "{}", responses[3]);
+    assert_eq!(responses[4], "=5");
+    assert_eq!(responses[5], "=6 1B2B1/B4B/6/W5/x4W/1W2W1 b 6");
+    assert_eq!(responses[6], "?7 unknown command");
+    assert_eq!(responses[7], "?8 'inf' is too long a time");
+    assert_eq!(responses[8], "?9 '1e400' is too long a time");
+    assert_eq!(responses[9], "=10");
+    assert_eq!(responses[10], "=");
+    assert_eq!(responses[11], "");
+}
@@ -0,0 +1,724 @@
+//! A full-screen terminal interface to play games and step through them,
+//! drawn with the escape codes of [`crate::ansi`].
+//!
+//! Moves are entered by moving a cursor over the board and selecting the amazon,
+//! its destination and the square of the arrow in turn, with the squares that can be
+//! selected highlighted. Players without a strategy are controlled that way,
+//! the others move on their own whenever the last position of the game is shown.
+//!
+//! Saved games can be loaded with [`Tui::from_record`] and played back at an adjustable speed.
+
+use crate::{
+    ansi::*,
+    game::{strat::*, *},
+};
+use std::{
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+const HELP: &str = "arrows/hjkl: cursor  enter: select  esc: cancel  ,/.: step  </>: start/end  \
+                    g: engine move  a: analyse  u: undo  p: pause  r: play back  +/-: speed  \
+                    number+enter: go to move  q: quit";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    /// Escape or backspace.
+    Back,
+    Char(char),
+}
+
+/// The part of a move that is selected next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Selection {
+    Amazon,
+    /// The destination of the amazon on the square.
+    Destination(Pos),
+    /// The arrow of the amazon moving between the squares.
+    Arrow(Pos, Pos),
+}
+
+pub struct Tui {
+    start: GameState,
+    moves: Vec<Move>,
+    /// How many of the moves are played in the position shown.
+    view: usize,
+    state: GameState,
+    /// The strategy of each player, `None` for players entering moves with the cursor.
+    players: [Option<Box<dyn Strategy>>; 2],
+    names: [String; 2],
+    /// Suggests moves for the evaluation pane and for players without strategy.
+    analysis: Box<dyn Strategy>,
+    /// The suggestion of `analysis` and the view it is for.
+    suggestion: Option<(usize, Move)>,
+    /// A resignation or failure after the last move.
+    forfeit: Option<(Player, Termination)>,
+    cursor: Pos,
+    selection: Selection,
+    /// Stops the strategies from moving on their own.
+    paused: bool,
+    /// When the last move was stepped to while playing the game back.
+    playback: Option<Instant>,
+    /// The time between moves when playing back.
+    delay: Duration,
+    /// The digits of a move number typed in to go to.
+    seek: String,
+    message: String,
+    quit: bool,
+}
+
+impl Tui {
+    pub fn new(
+        start: GameState,
+        white: Option<Box<dyn Strategy>>,
+        black: Option<Box<dyn Strategy>>,
+    ) -> Self {
+        let name = |player: &Option<Box<dyn Strategy>>| {
+            player
+                .as_ref()
+                .map_or_else(|| "Human".into(), |strategy| strategy.name())
+        };
+        Self {
+            state: start.clone(),
+            start,
+            moves: Vec::new(),
+            view: 0,
+            names: [name(&white), name(&black)],
+            players: [white, black],
+            analysis: Box::new(Minimax::with_depth(1)),
+            suggestion: None,
+            forfeit: None,
+            cursor: Pos::from((0, 0)),
+            selection: Selection::Amazon,
+            paused: false,
+            playback: None,
+            delay: Duration::from_millis(500),
+            seek: String::new(),
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    /// Loads a saved game to step through, showing its first position.
+    /// Moves can be played from any position, with nobody playing automatically.
+    pub fn from_record(record: &GameRecord) -> Result<Self, String> {
+        record.replay().map_err(|(i, err)| {
+            format!("move {} ({}) is illegal: {}", i + 1, record.moves[i], err)
+        })?;
+        let mut tui = Self::new(record.start.clone(), None, None);
+        tui.moves = record.moves.clone();
+        tui.names = [record.white.clone(), record.black.clone()];
+        tui.forfeit = match (record.winner, record.termination) {
+            (_, Termination::Normal | Termination::Unterminated) => None,
+            (Some(winner), termination) => Some((winner, termination)),
+            (None, _) => None,
+        };
+        Ok(tui)
+    }
+
+    /// Sets the strategy used to analyse positions.
+    pub fn with_analysis(mut self, analysis: Box<dyn Strategy>) -> Self {
+        self.analysis = analysis;
+        self
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The position shown.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    fn player_name(&self, player: Player) -> &str {
+        &self.names[player as usize]
+    }
+
+    fn at_end(&self) -> bool {
+        self.view == self.moves.len()
+    }
+
+    /// The winner and how the game ended, if it is over in the position shown.
+    fn outcome(&self) -> Option<(Player, Termination)> {
+        self.forfeit.filter(|_| self.at_end()).or_else(|| {
+            self.state
+                .is_finished()
+                .then(|| (self.state.turn().enemy(), Termination::Normal))
+        })
+    }
+
+    /// Shows the position after the first `view` moves.
+    fn set_view(&mut self, view: usize) {
+        self.view = view.min(self.moves.len());
+        self.state = self.start.clone();
+        for &mov in &self.moves[..self.view] {
+            self.state.do_move(mov);
+        }
+        self.selection = Selection::Amazon;
+    }
+
+    /// Plays a move in the position shown, replacing the moves that followed it.
+    fn play(&mut self, mov: Move) {
+        self.moves.truncate(self.view);
+        self.moves.push(mov);
+        self.forfeit = None;
+        self.set_view(self.moves.len());
+    }
+
+    /// Whether a strategy is about to move on its own.
+    pub fn engine_to_move(&self) -> bool {
+        self.at_end()
+            && !self.paused
+            && self.outcome().is_none()
+            && self.players[self.state.turn() as usize].is_some()
+    }
+
+    /// Lets the strategy of the player to move act, or the analysis strategy if it has none.
+    pub fn engine_move(&mut self) {
+        if self.outcome().is_some() {
+            self.message = "The game is over".into();
+            return;
+        }
+        let player = self.state.turn();
+        let strategy = self.players[player as usize]
+            .as_mut()
+            .unwrap_or(&mut self.analysis);
+        let name = strategy.name();
+        match strategy.act(&self.state) {
+            Ok(Action::Move(mov)) => match self.state.check_move(mov) {
+                Ok(()) => {
+                    self.play(mov);
+                    self.message = format!("{} played {}", name, mov);
+                }
+                Err(reason) => {
+                    self.message = format!("{} tried the illegal move {}: {}", name, mov, reason);
+                    self.moves.truncate(self.view);
+                    self.forfeit = Some((player.enemy(), Termination::IllegalMove));
+                }
+            },
+            Ok(Action::Resign) => {
+                self.message = format!("{} resigned", name);
+                self.moves.truncate(self.view);
+                self.forfeit = Some((player.enemy(), Termination::Resignation));
+            }
+            Ok(Action::Undo) => self.message = format!("{} wants to undo, press u", name),
+            Err(err) => {
+                self.message = format!("{} failed: {}", name, err);
+                self.paused = true;
+            }
+        }
+    }
+
+    /// Takes back moves until a player without strategy is to move,
+    /// or only the last move if all players have one.
+    fn undo(&mut self) {
+        let turn_at = |i: usize| {
+            if i.is_multiple_of(2) {
+                self.start.turn()
+            } else {
+                self.start.turn().enemy()
+            }
+        };
+        let target = (0..self.view)
+            .rev()
+            .find(|&i| self.players[turn_at(i) as usize].is_none())
+            .or_else(|| self.view.checked_sub(1));
+        match target {
+            Some(target) => {
+                self.moves.truncate(target);
+                self.forfeit = None;
+                self.set_view(target);
+                self.message = "Took back".into();
+            }
+            None => self.message = "Nothing to undo".into(),
+        }
+    }
+
+    /// The squares that can be selected next.
+    fn targets(&self) -> Bitboard {
+        let board = self.state.board();
+        let empty = board.empty();
+        match self.selection {
+            Selection::Amazon => board
+                .amazons(self.state.turn())
+                .into_iter()
+                .filter(|&a| !Bitboard::square(a).queen_moves(empty).is_empty())
+                .fold(Bitboard::EMPTY, |acc, a| acc | Bitboard::square(a)),
+            Selection::Destination(from) => Bitboard::square(from).queen_moves(empty),
+            Selection::Arrow(from, to) => {
+                Bitboard::square(to).queen_moves(empty ^ Bitboard::square(from))
+            }
+        }
+    }
+
+    fn select(&mut self) {
+        if self.outcome().is_some() {
+            self.message = "The game is over, step back or undo to play on".into();
+            return;
+        }
+        let cursor = self.cursor;
+        if !self.targets().has(cursor) {
+            self.message = format!("{} can't be selected", cursor);
+            return;
+        }
+        self.message.clear();
+        self.selection = match self.selection {
+            Selection::Amazon => Selection::Destination(cursor),
+            Selection::Destination(from) => Selection::Arrow(from, cursor),
+            Selection::Arrow(from, to) => {
+                self.play(Move {
+                    from,
+                    to,
+                    arrow: cursor,
+                });
+                Selection::Amazon
+            }
+        };
+    }
+
+    fn move_cursor(&mut self, (dx, dy): (i8, i8)) {
+        let last = self.state.board().size() as i8 - 1;
+        let x = (self.cursor.x as i8 + dx).clamp(0, last);
+        let y = (self.cursor.y as i8 + dy).clamp(0, last);
+        self.cursor = Pos::from((x as u8, y as u8));
+    }
+
+    /// Reacts to a key press.
+    pub fn handle(&mut self, key: Key) {
+        match key {
+            Key::Up | Key::Char('k') => self.move_cursor((0, -1)),
+            Key::Down | Key::Char('j') => self.move_cursor((0, 1)),
+            Key::Left | Key::Char('h') => self.move_cursor((-1, 0)),
+            Key::Right | Key::Char('l') => self.move_cursor((1, 0)),
+            Key::Enter if !self.seek.is_empty() => {
+                // to the position after black's move with that number
+                let offset = (self.start.turn() == Player::Black) as usize;
+                let view = self.seek.parse::<usize>().map_or(usize::MAX, |number| {
+                    number.saturating_mul(2).saturating_sub(offset)
+                });
+                self.seek.clear();
+                self.set_view(view);
+            }
+            Key::Enter => self.select(),
+            Key::Back if !self.seek.is_empty() => {
+                self.seek.pop();
+            }
+            Key::Back => {
+                self.selection = match self.selection {
+                    Selection::Arrow(from, _) => Selection::Destination(from),
+                    _ => Selection::Amazon,
+                }
+            }
+            Key::Char(',') => self.set_view(self.view.saturating_sub(1)),
+            Key::Char('.') => self.set_view(self.view + 1),
+            Key::Char('<') => self.set_view(0),
+            Key::Char('>') => self.set_view(self.moves.len()),
+            Key::Char('g') => self.engine_move(),
+            Key::Char('a') => {
+                if self.outcome().is_none() {
+                    self.suggestion = Some((self.view, self.analysis.find_move(&self.state)));
+                }
+            }
+            Key::Char('u') => self.undo(),
+            Key::Char('p') => self.paused = !self.paused,
+            Key::Char('r') => {
+                self.playback = match self.playback {
+                    Some(_) => None,
+                    None => {
+                        if self.at_end() {
+                            self.set_view(0);
+                        }
+                        Some(Instant::now())
+                    }
+                }
+            }
+            Key::Char('+') => self.delay = (self.delay / 2).max(Duration::from_millis(50)),
+            Key::Char('-') => self.delay = (self.delay * 2).min(Duration::from_secs(10)),
+            Key::Char(c @ '0'..='9') => self.seek.push(c),
+            Key::Char('q') => self.quit = true,
+            Key::Char(c) => self.message = format!("'{}' does nothing, see the keys below", c),
+        }
+    }
+
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Steps to the next move if the game is played back and it is time for it.
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(last) = self.playback {
+            if self.at_end() {
+                self.playback = None;
+            } else if now.duration_since(last) >= self.delay {
+                self.set_view(self.view + 1);
+                self.playback = Some(now);
+            }
+        }
+    }
+
+    fn board_lines(&self) -> Vec<String> {
+        let styles = BoardStyle::default();
+        let board = self.state.board();
+        let len = board.size();
+        let targets = self.targets();
+        let selected = match self.selection {
+            Selection::Amazon => Bitboard::EMPTY,
+            Selection::Destination(from) => Bitboard::square(from),
+            Selection::Arrow(from, to) => Bitboard::square(from) | Bitboard::square(to),
+        };
+        let width = (len - 1).to_string().len();
+
+        let mut lines = Vec::with_capacity(len + 1);
+        let mut header = format!("{:w$} ", "", w = width);
+        for x in 0..len as u8 {
+            header += &format!("{} ", (b'a' + x) as char);
+        }
+        lines.push(header);
+        for y in 0..len as u8 {
+            let mut line = format!("{:>w$} ", y, w = width);
+            for x in 0..len as u8 {
+                let pos = Pos::from((x, y));
+                let style = if pos == self.cursor {
+                    Style::from(Color::Yellow.bg_bright())
+                } else if selected.has(pos) {
+                    Style::from(Color::Cyan.bg())
+                } else if targets.has(pos) && self.outcome().is_none() {
+                    Style::from(Color::Green.bg_bright())
+                } else if x % 2 == y % 2 {
+                    styles.checker_light.clone()
+                } else {
+                    styles.checker_dark.clone()
+                };
+                line += &match board[pos] {
+                    Slot::Empty => style.fmt("  "),
+                    Slot::Arrow => style.combine(&styles.arrow).fmt("::"),
+                    Slot::Amazon(Player::White) => style.combine(&styles.amazon_light).fmt("()"),
+                    Slot::Amazon(Player::Black) => style.combine(&styles.amazon_dark).fmt("[]"),
+                };
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// The numbered moves, with the last move played in the position shown marked.
+    fn move_lines(&self, rows: usize) -> Vec<String> {
+        let offset = (self.start.turn() == Player::Black) as usize;
+        let mark = Style::from(Color::Black.fg()).with(Color::White.bg_bright());
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for i in 0..self.moves.len() + offset {
+            if i.is_multiple_of(2) {
+                line = format!("{:>3}.", i / 2 + 1);
+            }
+            line += &match i.checked_sub(offset) {
+                None => format!(" {:8}", "..."),
+                Some(j) if j + 1 == self.view => {
+                    format!(" {}", mark.fmt(format!("{:8}", self.moves[j].to_string())))
+                }
+                Some(j) => format!(" {:8}", self.moves[j].to_string()),
+            };
+            if i % 2 == 1 || i + 1 == self.moves.len() + offset {
+                lines.push(line.split_off(0));
+            }
+        }
+        // keeps the marked move in sight
+        let current = (self.view + offset).saturating_sub(1) / 2;
+        let first = (current + 1).saturating_sub(rows);
+        lines.into_iter().skip(first).take(rows).collect()
+    }
+
+    fn info_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "White: {}   Black: {}{}",
+            self.player_name(Player::White),
+            self.player_name(Player::Black),
+            if self.paused { "   (paused)" } else { "" }
+        )];
+
+        let position = format!("Move {} of {}", self.view, self.moves.len());
+        lines.push(match self.outcome() {
+            Some((winner, termination)) => format!(
+                "{}, {} {:?} wins ({})",
+                position,
+                record::result_str(Some(winner)),
+                winner,
+                termination
+            ),
+            None => format!("{}, {:?} to move", position, self.state.turn()),
+        });
+        let last = match self.view.checked_sub(1) {
+            Some(i) => format!(
+                "Last move: {} ({})",
+                self.moves[i],
+                self.moves[i].community_notation()
+            ),
+            None => "Last move: none".into(),
+        };
+        lines.push(match self.playback {
+            Some(_) => format!(
+                "{}   playing back, {:.2}s per move",
+                last,
+                self.delay.as_secs_f64()
+            ),
+            None => last,
+        });
+
+        let eval = match Reachability.eval(Player::White, self.state.board()) {
+            MIN_VAL => "White is trapped".into(),
+            MAX_VAL => "Black is trapped".into(),
+            eval => format!("{:+} for White", eval),
+        };
+        let suggestion = match self.suggestion {
+            Some((view, mov)) if view == self.view => {
+                format!("   {} suggests {}", self.analysis.name(), mov)
+            }
+            _ => String::new(),
+        };
+        lines.push(format!(
+            "Reachability: {}   {} moves{}",
+            eval,
+            self.state.moves().count(),
+            suggestion
+        ));
+
+        lines.push(match self.selection {
+            _ if self.outcome().is_some() => String::new(),
+            Selection::Amazon => format!("Select an amazon of {:?}", self.state.turn()),
+            Selection::Destination(from) => format!("Select where {} moves", from),
+            Selection::Arrow(from, to) => format!("Select where {}-{} shoots", from, to),
+        });
+        lines.push(match &self.seek[..] {
+            "" => self.message.clone(),
+            seek => format!("Go to move {}", seek),
+        });
+        lines.push(HELP.into());
+        lines
+    }
+
+    /// Draws the whole screen, with the board and the moves side by side above the rest.
+    pub fn render(&self) -> String {
+        let board = self.board_lines();
+        let moves = self.move_lines(board.len() - 1);
+
+        let mut lines = Vec::new();
+        for (i, line) in board.into_iter().enumerate() {
+            let side = match i {
+                0 => "Moves".into(),
+                _ => moves.get(i - 1).cloned().unwrap_or_default(),
+            };
+            lines.push(format!("{}   {}", line, side));
+        }
+        lines.push(String::new());
+        lines.extend(self.info_lines());
+
+        let mut buf = cursor_home();
+        for line in lines {
+            buf += &line;
+            buf += &clear_line_end();
+            buf += "\r\n";
+        }
+        buf + &clear_below()
+    }
+
+    /// Runs the interface in the terminal until it is quit.
+    pub fn run(&mut self) -> io::Result<()> {
+        let _raw = RawMode::enable()?;
+        let mut stdout = io::stdout();
+        let mut stdin = io::stdin();
+        write!(stdout, "{}{}", alternate_screen(true), show_cursor(false))?;
+
+        let result = (|| {
+            while !self.quit {
+                if self.engine_to_move() {
+                    self.message =
+                        format!("{} is thinking...", self.player_name(self.state.turn()));
+                    write!(stdout, "{}", self.render())?;
+                    stdout.flush()?;
+                    self.engine_move();
+                    continue;
+                }
+                write!(stdout, "{}", self.render())?;
+                stdout.flush()?;
+                while let Some(key) = read_key(&mut stdin)? {
+                    self.handle(key);
+                }
+                self.tick(Instant::now());
+            }
+            Ok(())
+        })();
+
+        write!(stdout, "{}{}", show_cursor(true), alternate_screen(false))?;
+        stdout.flush()?;
+        result
+    }
+}
+
+/// Reads the next key press, `None` if there was none in a short while.
+pub fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let mut next = || -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        Ok(match input.read(&mut byte)? {
+            0 => None,
+            _ => Some(byte[0]),
+        })
+    };
+    Ok(match next()? {
+        None => None,
+        Some(0x1b) => match next()? {
+            Some(b'[') | Some(b'O') => match next()? {
+                Some(b'A') => Some(Key::Up),
+                Some(b'B') => Some(Key::Down),
+                Some(b'C') => Some(Key::Right),
+                Some(b'D') => Some(Key::Left),
+                Some(b'H') => Some(Key::Char('<')),
+                Some(b'F') => Some(Key::Char('>')),
+                _ => Some(Key::Back),
+            },
+            _ => Some(Key::Back),
+        },
+        Some(b'\r') | Some(b'\n') | Some(b' ') => Some(Key::Enter),
+        Some(0x7f) | Some(0x08) => Some(Key::Back),
+        // Ctrl-C, which doesn't interrupt in raw mode
+        Some(0x03) => Some(Key::Char('q')),
+        Some(byte) => Some(Key::Char(byte as char)),
+    })
+}
+
+/// Puts the terminal into raw mode until dropped, with `stty` so no terminal library is needed.
+struct RawMode(String);
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        // reads return after a tenth of a second even without input, to notice a lone escape
+        stty(&["raw", "-echo", "min", "0", "time", "1"])?;
+        Ok(Self(saved.trim().into()))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.0]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(io::Error::other(format!(
+            "stty failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[test]
+fn tui_select_and_step() {
+    let mut tui = Tui::new(GameState::new(), None, None);
+    let press = |tui: &mut Tui, keys: &[Key]| keys.iter().for_each(|&key| tui.handle(key));
+    use Key::*;
+
+    // a6 -> a5, shooting back at a6
+    press(
+        &mut tui,
+        &[Down, Down, Down, Down, Down, Down, Enter, Up, Enter],
+    );
+    // not a queen move away from a5
+    press(&mut tui, &[Right, Right, Down, Enter]);
+    assert!(tui.moves().is_empty());
+    press(&mut tui, &[Left, Left, Enter]);
+    assert_eq!(tui.moves(), &["a6/a5/a6".parse::<Move>().unwrap()]);
+    assert_eq!(tui.state().turn(), Player::Black);
+
+    press(&mut tui, &[Char(',')]);
+    assert!(tui.state() == &GameState::new());
+    assert!(tui.render().contains("Move 0 of 1"));
+    press(&mut tui, &[Char('.')]);
+    assert_eq!(tui.state().turn(), Player::Black);
+
+    press(&mut tui, &[Char('u')]);
+    assert!(tui.moves().is_empty());
+}
+
+#[test]
+fn tui_keys() {
+    let mut input: &[u8] = b"\x1b[Aq\x1b";
+    let mut keys = Vec::new();
+    while let Some(key) = read_key(&mut input).unwrap() {
+        keys.push(key);
+    }
+    assert_eq!(keys, [Key::Up, Key::Char('q'), Key::Back]);
+}
+
+#[test]
+fn tui_replay() {
+    let mut state = GameState::with_size(6);
+    let moves = (0..6)
+        .map(|_| {
+            let mov = state.legal_moves()[0];
+            state.do_move(mov);
+            mov
+        })
+        .collect::<Vec<_>>();
+    let record = GameRecord {
+        white: "A".into(),
+        black: "B".into(),
+        date: String::new(),
+        winner: Some(Player::Black),
+        termination: Termination::Resignation,
+        start: GameState::with_size(6),
+        moves,
+        extra: Vec::new(),
+    };
+    let mut tui = Tui::from_record(&record).unwrap();
+    assert!(tui.render().contains("White: A   Black: B"));
+
+    for key in [Key::Char('2'), Key::Enter] {
+        tui.handle(key);
+    }
+    assert_eq!(tui.view, 4);
+    assert!(tui
+        .render()
+        .contains(&format!("Last move: {}", record.moves[3])));
+
+    let start = Instant::now();
+    tui.handle(Key::Char('r'));
+    tui.tick(start + Duration::from_millis(100));
+    assert_eq!(tui.view, 4);
+    tui.tick(start + Duration::from_secs(1));
+    tui.tick(start + Duration::from_secs(2));
+    assert_eq!(tui.view, 6);
+    assert!(tui.render().contains("0-1 Black wins (resignation)"));
+    tui.tick(start + Duration::from_secs(3));
+    assert!(tui.playback.is_none());
+
+    // starting with black, the first move number has only black's move
+    let mut black_first = record.clone();
+    black_first.start.do_move(black_first.moves.remove(0));
+    let mut tui = Tui::from_record(&black_first).unwrap();
+    for key in [Key::Char('2'), Key::Enter] {
+        tui.handle(key);
+    }
+    assert_eq!(tui.view, 3);
+    assert_eq!(tui.state().turn(), Player::White);
+    assert!(tui
+        .render()
+        .contains(&format!("Last move: {}", record.moves[3])));
+    for key in [Key::Char('9'), Key::Enter] {
+        tui.handle(key);
+    }
+    assert_eq!(tui.view, 5);
+}
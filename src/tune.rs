@@ -0,0 +1,224 @@
+//! Tunes the weights of a [`Weighted`] heuristic by self-play with a (1+1) evolution strategy:
+//! every generation, the best weights so far are changed randomly, and the new weights replace
+//! them if they win a match against them. The match is played with [`AlphaBeta`], which always
+//! finds the same move, from a few random opening moves, so the games differ from each other
+//! but are the same whenever a generation is played again.
+//!
+//! The state of the tuning is saved to a checkpoint file after every generation, like
+//!
+//! ```text
+//! generation 12
+//! step 0.35
+//! seed 0
+//! best Weighted(Mobility: 1.2..0.1, Territory: 0.3..1)
+//! ```
+//!
+//! and tuning continues from there when it is loaded again.
+
+use crate::{
+    count_wins_from,
+    game::{strat::*, *},
+};
+use rand::{prelude::*, rngs::StdRng};
+use std::{fs, io, path::Path};
+
+/// How much the step grows after a generation with better weights, and shrinks after one without.
+/// Grows four times as fast as it shrinks, so it settles where one in five generations succeeds.
+const GROW: f64 = 1.4;
+const SHRINK: f64 = 0.92;
+/// Weights are rounded to this many decimals, to keep the specs readable.
+const DECIMALS: i32 = 3;
+/// How many random plies the games of a generation start with.
+const OPENING: usize = 2;
+
+pub struct Tuner {
+    best: Weighted,
+    /// How far the weights are changed at most.
+    step: f64,
+    generation: usize,
+    /// How many pairs of games, one with each color from each opening, decide a generation.
+    games: usize,
+    depth: usize,
+    threads: usize,
+    start: GameState,
+    seed: u64,
+}
+
+impl Tuner {
+    pub fn new(weights: Weighted) -> Self {
+        Self {
+            best: weights,
+            step: 0.5,
+            generation: 0,
+            games: 10,
+            depth: 1,
+            threads: 1,
+            start: GameState::new(),
+            seed: 0,
+        }
+    }
+    pub fn games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+    /// The depth of the [`AlphaBeta`] searches that play with the weights.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+    /// The position the games start from, e.g. a smaller board to tune faster.
+    pub fn start(mut self, start: GameState) -> Self {
+        self.start = start;
+        self
+    }
+    /// The seed of the changes to the weights and of the openings.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn best(&self) -> &Weighted {
+        &self.best
+    }
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Tries new weights and tells whether they were better.
+    pub fn step(&mut self) -> bool {
+        // seeded by the generation, so a resumed tuning tries the same weights in the same games
+        let mut rng = StdRng::seed_from_u64(self.seed ^ self.generation as u64);
+        let scale = 10f64.powi(DECIMALS);
+        let weights = self
+            .best
+            .weights()
+            .into_iter()
+            .map(|weight| {
+                let weight = (weight + rng.gen_range(-1.0..=1.0) * self.step).max(0.0);
+                (weight * scale).round() / scale
+            })
+            .collect::<Vec<_>>();
+        let candidate = self.best.with_weights(&weights);
+
+        let openings = (0..self.games)
+            .map(|_| {
+                let mut opening = self.start.clone();
+                for _ in 0..OPENING {
+                    match opening.legal_moves().choose(&mut rng) {
+                        Some(&mov) => opening.do_move(mov),
+                        None => break,
+                    }
+                }
+                opening
+            })
+            .collect::<Vec<_>>();
+
+        let player = |weights: &Weighted| -> Box<dyn Strategy> {
+            Box::new(AlphaBeta::with_depth(self.depth).with_heuristic(weights.clone()))
+        };
+        let strats = [player(&self.best), player(&candidate)];
+        let wins = count_wins_from(&openings, &strats, 1, false, self.threads);
+        let (white, black) = wins[1][0];
+        // more than half of the games, one with each color per pair
+        let better = white + black > self.games;
+
+        if better {
+            self.best = candidate;
+            self.step *= GROW;
+        } else {
+            self.step *= SHRINK;
+        }
+        self.generation += 1;
+        better
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let checkpoint = format!(
+            "generation {}\nstep {}\nseed {}\nbest {}\n",
+            self.generation,
+            self.step,
+            self.seed,
+            self.best.name()
+        );
+        // written next to the old checkpoint first, so an interruption doesn't lose both
+        let path = path.as_ref();
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, checkpoint)?;
+        fs::rename(temporary, path)
+    }
+
+    /// Continues the tuning saved in the checkpoint at `path`.
+    pub fn load(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let checkpoint = fs::read_to_string(path)?;
+        let mut tuner = self;
+        for line in checkpoint.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let number = || {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| invalid(format!("'{}' is not a number", value)))
+            };
+            match key {
+                "generation" => tuner.generation = number()? as usize,
+                "step" => tuner.step = number()?,
+                "seed" => {
+                    tuner.seed = value
+                        .parse()
+                        .map_err(|_| invalid(format!("'{}' is not a seed", value)))?
+                }
+                "best" => tuner.best = value.parse().map_err(invalid)?,
+                _ => return Err(invalid(format!("unknown line '{}'", line))),
+            }
+        }
+        Ok(tuner)
+    }
+}
+
+#[test]
+fn tuning() {
+    let weights = "Weighted(Mobility: 1..0, Territory: 0..1)"
+        .parse::<Weighted>()
+        .unwrap();
+    let mut tuner = Tuner::new(weights)
+        .games(2)
+        .start(GameState::with_size(5))
+        .seed(3);
+    let (mut better, mut worse) = (0, 0);
+    for _ in 0..3 {
+        let (best, step) = (tuner.best().name(), tuner.step);
+        if tuner.step() {
+            better += 1;
+            assert_ne!(tuner.best().name(), best);
+            assert_eq!(tuner.step, step * GROW);
+        } else {
+            worse += 1;
+            assert_eq!(tuner.best().name(), best);
+            assert_eq!(tuner.step, step * SHRINK);
+        }
+    }
+    assert!(better > 0 && worse > 0);
+    assert_eq!(tuner.generation(), 3);
+
+    let path = std::env::temp_dir().join(format!("amazons-tuning-{}", std::process::id()));
+    tuner.save(&path).unwrap();
+    // the games and the start aren't saved, only the state of the tuning
+    let mut resumed = Tuner::new(Weighted::new())
+        .games(2)
+        .start(GameState::with_size(5))
+        .load(&path)
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(resumed.generation(), 3);
+    assert_eq!(resumed.step, tuner.step);
+    assert_eq!(resumed.seed, 3);
+    assert_eq!(resumed.best().name(), tuner.best().name());
+
+    // the games are the same, so a resumed tuning goes on like one that was never interrupted
+    assert_eq!(resumed.step(), tuner.step());
+    assert_eq!(resumed.best().name(), tuner.best().name());
+}